@@ -0,0 +1,53 @@
+// Build script shared by every binary in the workspace: each crate's build.rs is just
+// `include!("../build_info.rs")`, so the commit and build date for --version are computed
+// in one place
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Record the version, commit and date the binary was built from as BUILD_VERSION, so bug
+// reports can name them
+fn main() {
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!(
+        "cargo:rustc-env=BUILD_VERSION={} ({}, built {})",
+        std::env::var("CARGO_PKG_VERSION").unwrap_or_default(),
+        hash,
+        build_date()
+    );
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+/// Today's UTC date as YYYY-MM-DD, or SOURCE_DATE_EPOCH's for reproducible builds
+fn build_date() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs() as i64)
+                .unwrap_or(0)
+        });
+
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's days_from_civil, inverted)
+    let z = secs.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,403 @@
+use common::read_json_file;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CpuAirParams {
+    pub field: String,
+    pub stark: StarkParams,
+    pub use_extension_field: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StarkParams {
+    pub fri: FriParams,
+    pub log_n_cosets: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FriParams {
+    pub fri_step_list: Vec<u32>,
+    pub last_layer_degree_bound: u32,
+    pub n_queries: u32,
+    pub proof_of_work_bits: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The FRI fields of the params on their own, flat, as the prover's config file takes them
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProverFriConfig {
+    pub fri_step_list: Vec<u32>,
+    pub last_layer_degree_bound: u32,
+    pub n_queries: u32,
+    pub proof_of_work_bits: u32,
+}
+
+impl From<&FriParams> for ProverFriConfig {
+    fn from(fri: &FriParams) -> ProverFriConfig {
+        ProverFriConfig {
+            fri_step_list: fri.fri_step_list.clone(),
+            last_layer_degree_bound: fri.last_layer_degree_bound,
+            n_queries: fri.n_queries,
+            proof_of_work_bits: fri.proof_of_work_bits,
+        }
+    }
+}
+
+/// Inclusive range the on-chain verifier accepts for a FRI parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Bounds {
+    pub fn contains(&self, value: u32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// StarkVerifier.sol requires at least one query and at most MAX_N_QUERIES (48)
+pub const DEFAULT_N_QUERIES_BOUNDS: Bounds = Bounds { min: 1, max: 48 };
+
+/// StarkVerifier.sol caps proof_of_work_bits at 50; Deploy.s.sol sets the minimum to 20
+pub const DEFAULT_PROOF_OF_WORK_BITS_BOUNDS: Bounds = Bounds { min: 20, max: 50 };
+
+/// Describe each of `fri`'s n_queries and proof_of_work_bits that falls outside its bounds;
+/// empty when both are in range
+pub fn check_security_params(
+    fri: &FriParams,
+    n_queries: Bounds,
+    proof_of_work_bits: Bounds,
+) -> Vec<String> {
+    [
+        ("n_queries", fri.n_queries, n_queries),
+        (
+            "proof_of_work_bits",
+            fri.proof_of_work_bits,
+            proof_of_work_bits,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, value, bounds)| !bounds.contains(*value))
+    .map(|(name, value, bounds)| {
+        format!(
+            "{} {} is outside the verifier's accepted range {}..={}",
+            name, value, bounds.min, bounds.max
+        )
+    })
+    .collect()
+}
+
+/// Total FRI degree: log2(n_steps / degree_bound) rounded, plus 4
+pub fn fri_degree(n_steps: u32, degree_bound: u32) -> u32 {
+    ((n_steps as f64 / degree_bound as f64).log2().round() as u32) + 4
+}
+
+/// Split the FRI degree into a leading 0 step, as many steps of 4 as possible and a remainder
+pub fn calculate_fri_step_list(n_steps: u32, degree_bound: u32) -> Vec<u32> {
+    let fri_degree = fri_degree(n_steps, degree_bound);
+    let mut steps = vec![0];
+
+    // Add as many steps of size 4 as possible
+    let num_fours = fri_degree / 4;
+    steps.extend(vec![4; num_fours as usize]);
+
+    // Add remainder if any
+    let remainder = fri_degree % 4;
+    if remainder != 0 {
+        steps.push(remainder);
+    }
+
+    steps
+}
+
+/// Describe every position where `actual` differs from `expected`; empty when they match
+pub fn diff_fri_step_lists(expected: &[u32], actual: &[u32]) -> Vec<String> {
+    let mut diff = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e != a => {
+                diff.push(format!("  [{}]: expected {}, found {}", i, e, a))
+            }
+            (Some(e), None) => diff.push(format!("  [{}]: expected {}, missing", i, e)),
+            (None, Some(a)) => diff.push(format!("  [{}]: unexpected {}", i, a)),
+            _ => {}
+        }
+    }
+    diff
+}
+
+/// Nearest powers of two at or below and at or above `value`
+pub fn nearest_powers_of_two(value: u32) -> (u32, u32) {
+    if value <= 1 {
+        return (1, 1);
+    }
+    let lower = 1 << (31 - value.leading_zeros());
+    let upper = value.checked_next_power_of_two().unwrap_or(lower);
+    (lower, upper)
+}
+
+/// Update only `stark.fri.fri_step_list` and `stark.fri.last_layer_degree_bound`
+/// in a raw params document, leaving all other keys untouched.
+pub fn patch_fri_params(
+    params: &mut Value,
+    fri_step_list: &[u32],
+    degree_bound: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fri = params
+        .get_mut("stark")
+        .and_then(|stark| stark.get_mut("fri"))
+        .and_then(Value::as_object_mut)
+        .ok_or("Missing stark.fri object in params file")?;
+
+    fri.insert(
+        "fri_step_list".to_string(),
+        serde_json::to_value(fri_step_list)?,
+    );
+    fri.insert(
+        "last_layer_degree_bound".to_string(),
+        Value::from(degree_bound),
+    );
+
+    Ok(())
+}
+
+/// Read n_steps (or trace_length) from a public_input.json file
+pub fn read_n_steps_from_public_input(path: &Path) -> Result<u32, Box<dyn std::error::Error>> {
+    let json: Value = read_json_file(path)?;
+
+    // Try to find n_steps directly
+    if let Some(n_steps) = json.get("n_steps") {
+        return Ok(n_steps.as_u64().ok_or("Invalid n_steps")? as u32);
+    }
+
+    // Alternative: try trace_length
+    if let Some(trace_length) = json.get("trace_length") {
+        return Ok(trace_length.as_u64().ok_or("Invalid trace_length")? as u32);
+    }
+
+    // Try in public_memory
+    if let Some(trace_length) = json
+        .get("public_memory")
+        .and_then(|m| m.get("trace_length"))
+    {
+        return Ok(trace_length.as_u64().ok_or("Invalid trace_length")? as u32);
+    }
+
+    Err("Could not find n_steps or trace_length in public_input.json".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_temp_json(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "calculate_fri_steps_{}_{}.json",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn calculate_fri_step_list_table() {
+        let cases: &[(u32, u32, &[u32])] = &[
+            // n_steps == degree_bound: only the fixed 4
+            (64, 64, &[0, 4]),
+            // small trace, exact multiple of 4
+            (1024, 64, &[0, 4, 4]),
+            // remainder path
+            (32768, 64, &[0, 4, 4, 4, 1]),
+            (131072, 64, &[0, 4, 4, 4, 3]),
+            // large trace
+            (1 << 24, 64, &[0, 4, 4, 4, 4, 4, 2]),
+            // degree bound of 1
+            (1, 1, &[0, 4]),
+            (16, 1, &[0, 4, 4]),
+            (32, 1, &[0, 4, 4, 1]),
+        ];
+
+        for &(n_steps, degree_bound, expected) in cases {
+            assert_eq!(
+                calculate_fri_step_list(n_steps, degree_bound),
+                expected,
+                "n_steps={}, degree_bound={}",
+                n_steps,
+                degree_bound
+            );
+        }
+    }
+
+    #[test]
+    fn fri_step_list_sums_to_fri_degree() {
+        for &(n_steps, degree_bound) in &[(1024, 64), (32768, 64), (1 << 20, 1), (1 << 16, 16)] {
+            let steps = calculate_fri_step_list(n_steps, degree_bound);
+            assert_eq!(steps.iter().sum::<u32>(), fri_degree(n_steps, degree_bound));
+        }
+    }
+
+    #[test]
+    fn diff_fri_step_lists_reports_mismatches() {
+        assert!(diff_fri_step_lists(&[0, 4, 4, 1], &[0, 4, 4, 1]).is_empty());
+        assert_eq!(
+            diff_fri_step_lists(&[0, 4, 4, 1], &[0, 4, 3]),
+            vec![
+                "  [2]: expected 4, found 3".to_string(),
+                "  [3]: expected 1, missing".to_string(),
+            ]
+        );
+        assert_eq!(
+            diff_fri_step_lists(&[0, 4], &[0, 4, 2]),
+            vec!["  [2]: unexpected 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn nearest_powers_of_two_table() {
+        let cases = [
+            (0, (1, 1)),
+            (1, (1, 1)),
+            (2, (2, 2)),
+            (3, (2, 4)),
+            (64, (64, 64)),
+            (100, (64, 128)),
+            (u32::MAX, (1 << 31, 1 << 31)),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(nearest_powers_of_two(value), expected, "value={}", value);
+        }
+    }
+
+    #[test]
+    fn read_n_steps_from_public_input_keys() {
+        let cases = [
+            ("n_steps", r#"{"n_steps": 32768}"#, 32768),
+            ("trace_length", r#"{"trace_length": 1024}"#, 1024),
+            ("nested", r#"{"public_memory": {"trace_length": 512}}"#, 512),
+        ];
+
+        for (name, content, expected) in cases {
+            let path = write_temp_json(name, content);
+            assert_eq!(read_n_steps_from_public_input(&path).unwrap(), expected);
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn security_params_outside_bounds_are_reported() {
+        let fri = |n_queries, proof_of_work_bits| FriParams {
+            fri_step_list: vec![0, 4, 4, 4, 3],
+            last_layer_degree_bound: 64,
+            n_queries,
+            proof_of_work_bits,
+            extra: Map::new(),
+        };
+        let check = |fri: &FriParams| {
+            check_security_params(
+                fri,
+                DEFAULT_N_QUERIES_BOUNDS,
+                DEFAULT_PROOF_OF_WORK_BITS_BOUNDS,
+            )
+        };
+
+        assert!(check(&fri(16, 30)).is_empty());
+        assert!(check(&fri(1, 20)).is_empty());
+        assert!(check(&fri(48, 50)).is_empty());
+        assert_eq!(
+            check(&fri(0, 30)),
+            vec!["n_queries 0 is outside the verifier's accepted range 1..=48"]
+        );
+        assert_eq!(
+            check(&fri(49, 51)),
+            vec![
+                "n_queries 49 is outside the verifier's accepted range 1..=48",
+                "proof_of_work_bits 51 is outside the verifier's accepted range 20..=50",
+            ]
+        );
+        assert_eq!(
+            check_security_params(
+                &fri(16, 10),
+                DEFAULT_N_QUERIES_BOUNDS,
+                Bounds { min: 0, max: 50 }
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn prover_config_has_only_the_fri_fields() {
+        let params: CpuAirParams = serde_json::from_str(
+            r#"{
+                "field": "PrimeField0",
+                "stark": {
+                    "fri": {
+                        "fri_step_list": [0, 4, 4, 3],
+                        "last_layer_degree_bound": 128,
+                        "n_queries": 18,
+                        "proof_of_work_bits": 24,
+                        "fri_hint": "custom"
+                    },
+                    "log_n_cosets": 4
+                },
+                "use_extension_field": false
+            }"#,
+        )
+        .unwrap();
+
+        let config = serde_json::to_value(ProverFriConfig::from(&params.stark.fri)).unwrap();
+        assert_eq!(
+            config,
+            serde_json::json!({
+                "fri_step_list": [0, 4, 4, 3],
+                "last_layer_degree_bound": 128,
+                "n_queries": 18,
+                "proof_of_work_bits": 24
+            })
+        );
+    }
+
+    #[test]
+    fn read_n_steps_from_public_input_missing() {
+        let path = write_temp_json("missing", r#"{"layout": "starknet"}"#);
+        assert!(read_n_steps_from_public_input(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn cpu_air_params_round_trip_keeps_unknown_fields() {
+        let input = r#"{
+            "field": "PrimeField0",
+            "stark": {
+                "fri": {
+                    "fri_step_list": [0, 4, 4, 4, 3],
+                    "last_layer_degree_bound": 64,
+                    "n_queries": 16,
+                    "proof_of_work_bits": 30,
+                    "fri_hint": "custom"
+                },
+                "log_n_cosets": 2,
+                "stark_hint": 7
+            },
+            "use_extension_field": false,
+            "prover_hint": { "nested": true }
+        }"#;
+
+        let params: CpuAirParams = serde_json::from_str(input).unwrap();
+        let output: Value = serde_json::to_value(&params).unwrap();
+
+        assert_eq!(output["stark"]["fri"]["fri_hint"], "custom");
+        assert_eq!(output["stark"]["stark_hint"], 7);
+        assert_eq!(output["prover_hint"]["nested"], true);
+    }
+}
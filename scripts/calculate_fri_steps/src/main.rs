@@ -1,13 +1,18 @@
+use calculate_fri_steps::{
+    calculate_fri_step_list, check_security_params, diff_fri_step_lists, fri_degree,
+    nearest_powers_of_two, patch_fri_params, read_n_steps_from_public_input, Bounds, CpuAirParams,
+    ProverFriConfig, DEFAULT_N_QUERIES_BOUNDS, DEFAULT_PROOF_OF_WORK_BITS_BOUNDS,
+};
 use clap::Parser;
-use serde::{Deserialize, Serialize};
+use common::read_json_file;
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about = "Calculate FRI step sizes for STARK proofs", long_about = None)]
+#[command(author, version = env!("BUILD_VERSION"), about = "Calculate FRI step sizes for STARK proofs", long_about = None)]
 struct Args {
-    /// Path to cpu_air_params.json file
+    /// Path to cpu_air_params.json file, or - to read it from stdin
     #[arg(short, long)]
     params_file: PathBuf,
 
@@ -15,6 +20,10 @@ struct Args {
     #[arg(short, long)]
     degree_bound: Option<u32>,
 
+    /// Compare several degree bounds (comma-separated) in a table instead of updating the file
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["degree_bound", "verify", "patch"])]
+    degree_bounds: Vec<u32>,
+
     /// Number of steps (if not specified, calculates from trace length)
     #[arg(short, long)]
     n_steps: Option<u32>,
@@ -23,89 +32,176 @@ struct Args {
     #[arg(long)]
     public_input: Option<PathBuf>,
 
-    /// Output file (if not specified, updates input file)
+    /// Output file, or - for stdout (if not specified, updates input file; params read from
+    /// stdin go to stdout)
     #[arg(short, long)]
     output: Option<PathBuf>,
 
     /// Just print the calculated steps without modifying file
     #[arg(long)]
     dry_run: bool,
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CpuAirParams {
-    field: String,
-    stark: StarkParams,
-    use_extension_field: bool,
-}
+    /// Only patch the FRI fields in place, keeping every other key and its ordering
+    #[arg(long)]
+    patch: bool,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct StarkParams {
-    fri: FriParams,
-    log_n_cosets: u32,
-}
+    /// Also write the resulting FRI fields on their own to this file, in the prover's config
+    /// format
+    #[arg(long, conflicts_with_all = ["dry_run", "verify", "degree_bounds"])]
+    emit_prover_config: Option<PathBuf>,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct FriParams {
-    fri_step_list: Vec<u32>,
-    last_layer_degree_bound: u32,
-    n_queries: u32,
-    proof_of_work_bits: u32,
-}
+    /// Check that the fri_step_list in the file matches the calculated one, without modifying it
+    #[arg(long, conflicts_with_all = ["dry_run", "patch", "output"])]
+    verify: bool,
 
-fn calculate_fri_step_list(n_steps: u32, degree_bound: u32) -> Vec<u32> {
-    let fri_degree = ((n_steps as f64 / degree_bound as f64).log2().round() as u32) + 4;
-    let mut steps = vec![0];
+    /// Fail instead of warning when the degree bound is not a power of two, or n_queries or
+    /// proof_of_work_bits is outside the verifier's accepted range
+    #[arg(long)]
+    strict: bool,
 
-    // Add as many steps of size 4 as possible
-    let num_fours = fri_degree / 4;
-    steps.extend(vec![4; num_fours as usize]);
+    /// Smallest n_queries the verifier accepts
+    #[arg(long, default_value_t = DEFAULT_N_QUERIES_BOUNDS.min)]
+    min_n_queries: u32,
 
-    // Add remainder if any
-    let remainder = fri_degree % 4;
-    if remainder != 0 {
-        steps.push(remainder);
-    }
+    /// Largest n_queries the verifier accepts
+    #[arg(long, default_value_t = DEFAULT_N_QUERIES_BOUNDS.max)]
+    max_n_queries: u32,
 
-    steps
+    /// Smallest proof_of_work_bits the verifier accepts
+    #[arg(long, default_value_t = DEFAULT_PROOF_OF_WORK_BITS_BOUNDS.min)]
+    min_pow_bits: u32,
+
+    /// Largest proof_of_work_bits the verifier accepts
+    #[arg(long, default_value_t = DEFAULT_PROOF_OF_WORK_BITS_BOUNDS.max)]
+    max_pow_bits: u32,
+
+    /// Reject n_steps above this bound, to catch a mistyped --n-steps
+    #[arg(long, default_value_t = DEFAULT_MAX_N_STEPS)]
+    max_n_steps: u32,
+}
+
+/// Print a status line, on stderr when stdout carries the updated params
+macro_rules! status {
+    ($to_stdout:expr) => {
+        if $to_stdout {
+            eprintln!();
+        } else {
+            println!();
+        }
+    };
+    ($to_stdout:expr, $($arg:tt)*) => {
+        if $to_stdout {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
 }
 
-fn read_n_steps_from_public_input(path: &PathBuf) -> Result<u32, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(path)?;
-    let json: Value = serde_json::from_str(&content)?;
+/// Path standing for stdin as --params-file and stdout as --output
+const STDIO_PATH: &str = "-";
 
-    // Try to find n_steps directly
-    if let Some(n_steps) = json.get("n_steps") {
-        return Ok(n_steps.as_u64().ok_or("Invalid n_steps")? as u32);
-    }
+/// Default --max-n-steps: 2^28 steps, well beyond what the prover handles in practice
+const DEFAULT_MAX_N_STEPS: u32 = 1 << 28;
 
-    // Alternative: try trace_length
-    if let Some(trace_length) = json.get("trace_length") {
-        return Ok(trace_length.as_u64().ok_or("Invalid trace_length")? as u32);
+/// The FRI verifier reverts on-chain unless last_layer_degree_bound is a power of two
+fn check_degree_bound(
+    source: &str,
+    degree_bound: u32,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if degree_bound.is_power_of_two() {
+        return Ok(());
     }
 
-    // Try in public_memory
-    if let Some(trace_length) = json
-        .get("public_memory")
-        .and_then(|m| m.get("trace_length"))
-    {
-        return Ok(trace_length.as_u64().ok_or("Invalid trace_length")? as u32);
+    let (lower, upper) = nearest_powers_of_two(degree_bound);
+    let message = format!(
+        "{} degree bound {} is not a power of two (nearest valid values: {}, {})",
+        source, degree_bound, lower, upper
+    );
+
+    if strict {
+        return Err(message.into());
     }
+    eprintln!("Warning: {}", message);
+    Ok(())
+}
+
+/// Print the FRI step list each degree bound would produce for `n_steps`
+fn print_degree_bound_table(n_steps: u32, degree_bounds: &[u32]) {
+    let rows: Vec<(u32, u32, String, usize)> = degree_bounds
+        .iter()
+        .map(|&degree_bound| {
+            let steps = calculate_fri_step_list(n_steps, degree_bound);
+            (
+                degree_bound,
+                fri_degree(n_steps, degree_bound),
+                format!("{:?}", steps),
+                steps.len(),
+            )
+        })
+        .collect();
+    let list_width = rows
+        .iter()
+        .map(|(_, _, list, _)| list.len())
+        .max()
+        .unwrap_or(0)
+        .max("fri_step_list".len());
 
-    Err("Could not find n_steps or trace_length in public_input.json".into())
+    println!("FRI step lists for n_steps {}:", n_steps);
+    println!(
+        "  {:>12}  {:>10}  {:<list_width$}  {:>6}",
+        "degree_bound",
+        "fri_degree",
+        "fri_step_list",
+        "layers",
+        list_width = list_width
+    );
+    for (degree_bound, fri_degree, list, layers) in &rows {
+        println!(
+            "  {:>12}  {:>10}  {:<list_width$}  {:>6}",
+            degree_bound,
+            fri_degree,
+            list,
+            layers,
+            list_width = list_width
+        );
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    let from_stdin = args.params_file.as_os_str() == STDIO_PATH;
+    let to_stdout = match &args.output {
+        Some(output) => output.as_os_str() == STDIO_PATH,
+        None => from_stdin,
+    };
+    let params_source = if from_stdin {
+        "stdin".to_string()
+    } else {
+        args.params_file.display().to_string()
+    };
 
-    // Read the params file
-    let params_content = fs::read_to_string(&args.params_file)?;
-    let mut params: CpuAirParams = serde_json::from_str(&params_content)?;
+    // Read the params file, keeping the raw document for --patch
+    let mut raw_params: Value = if from_stdin {
+        serde_json::from_reader(std::io::stdin().lock())?
+    } else {
+        read_json_file(&args.params_file)?
+    };
+    let mut params: CpuAirParams = serde_json::from_value(raw_params.clone())
+        .map_err(|e| format!("Failed to parse {}: {}", params_source, e))?;
 
     // Get degree_bound (from args, file, or default)
-    let degree_bound = args
-        .degree_bound
-        .unwrap_or(params.stark.fri.last_layer_degree_bound);
+    let file_degree_bound = params.stark.fri.last_layer_degree_bound;
+    check_degree_bound(
+        &params_source,
+        file_degree_bound,
+        args.strict && args.degree_bound.is_none(),
+    )?;
+    if let Some(degree_bound) = args.degree_bound {
+        check_degree_bound("--degree-bound", degree_bound, args.strict)?;
+    }
+    let degree_bound = args.degree_bound.unwrap_or(file_degree_bound);
 
     // Get n_steps (from args, public_input, or default)
     let n_steps = if let Some(n) = args.n_steps {
@@ -113,7 +209,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else if let Some(ref public_input_path) = args.public_input {
         match read_n_steps_from_public_input(public_input_path) {
             Ok(n_steps) => {
-                println!(
+                status!(
+                    to_stdout,
                     "Read n_steps from {}: {}",
                     public_input_path.display(),
                     n_steps
@@ -130,31 +227,125 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         panic!("No n_steps provided and could not read from public_input.json");
     };
 
+    if n_steps > args.max_n_steps {
+        return Err(format!(
+            "n_steps {} exceeds --max-n-steps {}; check for a mistyped value or raise the bound",
+            n_steps, args.max_n_steps
+        )
+        .into());
+    }
+
+    if !args.degree_bounds.is_empty() {
+        if args.output.is_some() {
+            return Err(
+                "--output is ambiguous with --degree-bounds; pick one bound with --degree-bound"
+                    .into(),
+            );
+        }
+        for &degree_bound in &args.degree_bounds {
+            check_degree_bound("--degree-bounds", degree_bound, args.strict)?;
+        }
+        print_degree_bound_table(n_steps, &args.degree_bounds);
+        return Ok(());
+    }
+
     // Calculate FRI steps
     let new_fri_steps = calculate_fri_step_list(n_steps, degree_bound);
 
-    println!("Calculating FRI step list:");
-    println!("  n_steps: {}", n_steps);
-    println!("  degree_bound: {}", degree_bound);
-    println!(
+    status!(to_stdout, "Calculating FRI step list:");
+    status!(to_stdout, "  n_steps: {}", n_steps);
+    status!(to_stdout, "  degree_bound: {}", degree_bound);
+    status!(
+        to_stdout,
         "  fri_degree: {}",
-        ((n_steps as f64 / degree_bound as f64).log2().round() as u32) + 4
+        fri_degree(n_steps, degree_bound)
     );
-    println!("  calculated fri_step_list: {:?}", new_fri_steps);
-    println!();
+    status!(to_stdout, "  calculated fri_step_list: {:?}", new_fri_steps);
+    status!(to_stdout);
+
+    let n_queries_bounds = Bounds {
+        min: args.min_n_queries,
+        max: args.max_n_queries,
+    };
+    let pow_bits_bounds = Bounds {
+        min: args.min_pow_bits,
+        max: args.max_pow_bits,
+    };
+    status!(to_stdout, "Checking security params:");
+    status!(
+        to_stdout,
+        "  n_queries: {} (accepted {}..={})",
+        params.stark.fri.n_queries,
+        n_queries_bounds.min,
+        n_queries_bounds.max
+    );
+    status!(
+        to_stdout,
+        "  proof_of_work_bits: {} (accepted {}..={})",
+        params.stark.fri.proof_of_work_bits,
+        pow_bits_bounds.min,
+        pow_bits_bounds.max
+    );
+    status!(to_stdout);
+    let problems = check_security_params(&params.stark.fri, n_queries_bounds, pow_bits_bounds);
+    if !problems.is_empty() {
+        if args.strict {
+            return Err(format!("{} {}", params_source, problems.join("; ")).into());
+        }
+        for problem in &problems {
+            eprintln!("Warning: {} {}", params_source, problem);
+        }
+    }
+
+    if args.verify {
+        let diff = diff_fri_step_lists(&new_fri_steps, &params.stark.fri.fri_step_list);
+        if diff.is_empty() {
+            println!("✓ {} is up to date", params_source);
+            return Ok(());
+        }
+
+        eprintln!(
+            "✗ fri_step_list in {} does not match the calculated list",
+            params_source
+        );
+        eprintln!("  expected: {:?}", new_fri_steps);
+        eprintln!("  found:    {:?}", params.stark.fri.fri_step_list);
+        for line in &diff {
+            eprintln!("{}", line);
+        }
+        return Err("fri_step_list verification failed".into());
+    }
 
     if args.dry_run {
-        println!("Dry run - not modifying files");
+        status!(to_stdout, "Dry run - not modifying files");
         return Ok(());
     }
 
-    // Update params
-    params.stark.fri.fri_step_list = new_fri_steps;
-    params.stark.fri.last_layer_degree_bound = degree_bound;
+    if let Some(config_path) = &args.emit_prover_config {
+        let mut fri = ProverFriConfig::from(&params.stark.fri);
+        fri.fri_step_list = new_fri_steps.clone();
+        fri.last_layer_degree_bound = degree_bound;
+        fs::write(config_path, serde_json::to_string_pretty(&fri)?)?;
+        status!(to_stdout, "✓ Wrote prover config {}", config_path.display());
+    }
+
+    let output_content = if args.patch {
+        // Patch the raw document so fields not modeled by CpuAirParams survive
+        patch_fri_params(&mut raw_params, &new_fri_steps, degree_bound)?;
+        serde_json::to_string_pretty(&raw_params)?
+    } else {
+        // Update params
+        params.stark.fri.fri_step_list = new_fri_steps;
+        params.stark.fri.last_layer_degree_bound = degree_bound;
+        serde_json::to_string_pretty(&params)?
+    };
 
     // Write output
+    if to_stdout {
+        println!("{}", output_content);
+        return Ok(());
+    }
     let output_path = args.output.unwrap_or(args.params_file);
-    let output_content = serde_json::to_string_pretty(&params)?;
     fs::write(&output_path, output_content)?;
 
     println!("✓ Updated {}", output_path.display());
@@ -0,0 +1,173 @@
+//! Where the deploy script writes its results and how they map to deployment-addresses.json
+
+use crate::read_json_file;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEPLOY_SCRIPT: &str = "script/Deploy.s.sol:DeployScript";
+pub const DEPLOYMENT_ADDRESSES_FILE: &str = "deployment-addresses.json";
+
+/// Contract names in the forge broadcast mapped to their deployment-addresses.json keys and sources
+pub const DEPLOYED_CONTRACTS: &[(&str, &str, &str)] = &[
+    (
+        "CpuVerifier",
+        "verifier",
+        "src/layout_starknet/CpuVerifier.sol",
+    ),
+    (
+        "GpsStatementVerifier",
+        "gpsVerifier",
+        "src/GpsStatementVerifier/GpsStatementVerifier.sol",
+    ),
+    (
+        "MemoryPageFactRegistry",
+        "factRegistry",
+        "src/common/MemoryPageFactRegistry.sol",
+    ),
+    ("CpuOods", "oodsContract", "src/layout_starknet/CpuOods.sol"),
+    (
+        "CpuConstraintPoly",
+        "constraintPoly",
+        "src/layout_starknet/CpuConstraintPoly.sol",
+    ),
+    (
+        "CairoBootloaderProgram",
+        "bootloaderProgram",
+        "src/common/CairoBootloaderProgram.sol",
+    ),
+    (
+        "MerkleStatementContract",
+        "merkleStatementContract",
+        "src/common/MerkleStatementContract.sol",
+    ),
+    (
+        "FriStatementContract",
+        "friStatementContract",
+        "src/common/FriStatementContract.sol",
+    ),
+];
+
+/// Most recently written `run-latest.json` for the deploy script, across all chain ids
+pub fn latest_broadcast_file() -> Option<PathBuf> {
+    latest_run_file(Path::new("run-latest.json"))
+}
+
+/// Most recently written simulation (`dry-run/run-latest.json`) for the deploy script,
+/// across all chain ids
+pub fn latest_dry_run_file() -> Option<PathBuf> {
+    latest_run_file(&Path::new("dry-run").join("run-latest.json"))
+}
+
+fn latest_run_file(relative: &Path) -> Option<PathBuf> {
+    let script_file = DEPLOY_SCRIPT.split(':').next()?;
+    let script_name = Path::new(script_file).file_name()?;
+    let broadcast_dir = Path::new("broadcast").join(script_name);
+
+    fs::read_dir(broadcast_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(relative))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// `(contractName, contractAddress)` for every contract created (CREATE or CREATE2) in a
+/// forge broadcast or simulation
+pub fn deployed_contracts(
+    broadcast_path: &Path,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let broadcast: Value = read_json_file(broadcast_path)?;
+    let transactions = broadcast
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or("Broadcast file has no transactions")?;
+
+    Ok(transactions
+        .iter()
+        .filter(|tx| {
+            matches!(
+                tx.get("transactionType").and_then(Value::as_str),
+                Some("CREATE") | Some("CREATE2")
+            )
+        })
+        .filter_map(|tx| {
+            let name = tx.get("contractName")?.as_str()?;
+            let address = tx.get("contractAddress")?.as_str()?;
+            Some((name.to_string(), address.to_string()))
+        })
+        .collect())
+}
+
+/// Chain id and every transaction hash recorded in a forge broadcast, in send order
+pub fn broadcast_transactions(
+    broadcast_path: &Path,
+) -> Result<(Option<u64>, Vec<String>), Box<dyn std::error::Error>> {
+    let broadcast: Value = read_json_file(broadcast_path)?;
+    let hashes = broadcast
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or("Broadcast file has no transactions")?
+        .iter()
+        .filter_map(|tx| tx.get("hash")?.as_str().map(str::to_string))
+        .collect();
+    Ok((broadcast.get("chain").and_then(Value::as_u64), hashes))
+}
+
+/// What a forge simulation of the deploy script would broadcast
+#[derive(Debug)]
+pub struct SimulatedDeployment {
+    pub chain_id: Option<u64>,
+    /// Sender of the first transaction, the deployer
+    pub from: Option<String>,
+    pub transactions: usize,
+}
+
+/// Chain id, sender and transaction count of a forge simulation (`dry-run/run-latest.json`)
+pub fn simulated_deployment(
+    dry_run_path: &Path,
+) -> Result<SimulatedDeployment, Box<dyn std::error::Error>> {
+    let simulation: Value = read_json_file(dry_run_path)?;
+    let transactions = simulation
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or("Simulation file has no transactions")?;
+    let from = transactions
+        .first()
+        .and_then(|tx| tx.get("transaction")?.get("from")?.as_str())
+        .map(str::to_string);
+    Ok(SimulatedDeployment {
+        chain_id: simulation.get("chain").and_then(Value::as_u64),
+        from,
+        transactions: transactions.len(),
+    })
+}
+
+/// deployment-addresses.json key for a deployed contract.
+///
+/// Contracts without a known key (e.g. periodic columns) are stored under their own name.
+pub fn deployment_key(name: &str) -> &str {
+    DEPLOYED_CONTRACTS
+        .iter()
+        .find(|(contract, _, _)| *contract == name)
+        .map(|(_, key, _)| *key)
+        .unwrap_or(name)
+}
+
+/// deployment-addresses.json contents for a set of `(contractName, contractAddress)` pairs
+pub fn deployment_addresses(contracts: &[(String, String)]) -> Value {
+    let addresses: Map<String, Value> = contracts
+        .iter()
+        .map(|(name, address)| {
+            (
+                deployment_key(name).to_string(),
+                Value::String(address.clone()),
+            )
+        })
+        .collect();
+    Value::Object(addresses)
+}
@@ -0,0 +1,547 @@
+pub mod deployment;
+pub mod output;
+pub mod transport;
+
+use ethers::{
+    core::k256::ecdsa::SigningKey,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware},
+    signers::{LocalWallet, Signer},
+    utils::hex,
+};
+use flate2::read::MultiGzDecoder;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use transport::RpcProvider;
+use zeroize::Zeroizing;
+
+/// Config file looked up in the working directory when --config is not given
+pub const DEFAULT_CONFIG_FILE: &str = "playground.toml";
+
+/// Settings shared by all scripts, loaded from `playground.toml`.
+///
+/// Every value is optional; CLI flags and env vars take precedence over the file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// RPC URLs keyed by network: `sepolia`, `base-sepolia`, or a custom network's env var name
+    pub rpc_urls: HashMap<String, String>,
+    pub contracts: ContractsConfig,
+    pub paths: PathsConfig,
+    pub gas: GasConfig,
+    /// Confirmations to wait for on each transaction
+    pub confirmations: Option<usize>,
+    /// Path to the forge binary
+    pub forge_bin: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContractsConfig {
+    pub gps_verifier: Option<String>,
+    pub merkle_statement: Option<String>,
+    pub fri_statement: Option<String>,
+    pub memory_registry: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    pub annotated_proof: Option<String>,
+    pub input_json: Option<String>,
+    pub fact_topologies: Option<String>,
+}
+
+/// Fees in wei
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GasConfig {
+    pub gas_price: Option<u128>,
+    pub priority_gas_price: Option<u128>,
+    pub with_gas_price: Option<u128>,
+}
+
+/// Why a JSON file couldn't be loaded, with the file it was
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file was read but isn't the JSON expected
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl ConfigError {
+    /// Whether the file doesn't exist, for callers where that's fine
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ConfigError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound)
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "Failed to parse {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Open `path` for buffered reading, decompressing `.gz` (gzip) and `.zst` (zstd) files on
+/// the fly
+pub fn open_input(path: &Path) -> std::io::Result<Box<dyn Read>> {
+    let file = fs::File::open(path)?;
+    Ok(match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz") => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+        Some("zst") => Box::new(BufReader::new(zstd::stream::read::Decoder::new(file)?)),
+        _ => Box::new(BufReader::new(file)),
+    })
+}
+
+/// Read `path` into a string, decompressing `.gz` and `.zst` files
+pub fn read_input_to_string(path: &Path) -> std::io::Result<String> {
+    let mut content = String::new();
+    open_input(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Read and deserialize the JSON file at `path`, with errors that name it. `.gz` and `.zst`
+/// files are decompressed, and the file is parsed as it's read so a large one is never
+/// also held in memory as a string
+pub fn read_json_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, ConfigError> {
+    let path = path.as_ref();
+    let reader = open_input(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_reader(reader).map_err(|source| {
+        if source.is_io() {
+            ConfigError::Io {
+                path: path.to_path_buf(),
+                source: source.into(),
+            }
+        } else {
+            ConfigError::Parse {
+                path: path.to_path_buf(),
+                source,
+            }
+        }
+    })
+}
+
+/// Load the variables in dotenv file `path` into the environment, replacing any the shell
+/// (or direnv) already set, so `--env-file` picks the whole environment; CLI flags still
+/// override them
+pub fn load_env_file(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::from_path_override(path)
+        .map_err(|e| format!("Failed to load env file {}: {}", path.display(), e))?;
+    // On stderr, so it stays out of --json output on stdout
+    eprintln!("Loaded environment from {}", path.display());
+    Ok(())
+}
+
+impl Config {
+    /// Load the config from `path`, or from `playground.toml` in the working directory if it exists.
+    ///
+    /// An explicit path must exist; a missing default file yields an empty config.
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn std::error::Error>> {
+        let path: PathBuf = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+                if !default.is_file() {
+                    return Ok(Config::default());
+                }
+                default
+            }
+        };
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+        let config = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse config {}: {}", path.display(), e))?;
+        eprintln!("Loaded config from {}", path.display());
+        Ok(config)
+    }
+
+    /// RPC URL configured for `network`, if any
+    pub fn rpc_url(&self, network: &Network) -> Option<&str> {
+        let key = match network {
+            Network::Sepolia => "sepolia",
+            Network::BaseSepolia => "base-sepolia",
+            Network::Custom { rpc_env } => rpc_env.as_str(),
+        };
+        self.rpc_urls.get(key).map(|s| s.as_str())
+    }
+}
+
+/// Network selection shared by the deploy and verify binaries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Network {
+    Sepolia,
+    BaseSepolia,
+    /// Any other network, with the RPC URL read from the named env var
+    Custom {
+        rpc_env: String,
+    },
+}
+
+impl Network {
+    /// Environment variable the RPC URL is read from when no override is given
+    pub fn rpc_env(&self) -> &str {
+        match self {
+            Network::Sepolia => "SEPOLIA_RPC_URL",
+            Network::BaseSepolia => "BASE_SEPOLIA_RPC_URL",
+            Network::Custom { rpc_env } => rpc_env,
+        }
+    }
+
+    /// Chain name as understood by forge (`--chain`); None for custom networks
+    pub fn chain(&self) -> Option<&'static str> {
+        match self {
+            Network::Sepolia => Some("sepolia"),
+            Network::BaseSepolia => Some("base-sepolia"),
+            Network::Custom { .. } => None,
+        }
+    }
+
+    /// Chain id the network is expected to report; None for custom networks
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            Network::Sepolia => Some(11155111),
+            Network::BaseSepolia => Some(84532),
+            Network::Custom { .. } => None,
+        }
+    }
+
+    /// The network's name with the chain id it reported, for messages
+    pub fn describe(&self, chain_id: u64) -> String {
+        match self.chain() {
+            Some(chain) => format!("{} (chain id {})", chain, chain_id),
+            None => format!("chain id {}", chain_id),
+        }
+    }
+}
+
+/// Resolve the RPC URL: an explicit override wins over the network's env var, which wins
+/// over the config file
+pub fn resolve_rpc_url(
+    network: &Network,
+    rpc_url_override: Option<&str>,
+    config: &Config,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(url) = rpc_url_override {
+        return Ok(url.to_string());
+    }
+
+    let var = network.rpc_env();
+    env::var(var)
+        .ok()
+        .or_else(|| config.rpc_url(network).map(|s| s.to_string()))
+        .ok_or_else(|| {
+            format!(
+                "{} must be set in .env, in {} or use --rpc-url <url>",
+                var, DEFAULT_CONFIG_FILE
+            )
+            .into()
+        })
+}
+
+/// Parse `KEY=VALUE` RPC headers from the command line, falling back to RPC_HEADERS
+/// (comma-separated `KEY=VALUE` pairs) when none are given
+pub fn resolve_rpc_headers(
+    cli_headers: &[String],
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let entries: Vec<String> = if cli_headers.is_empty() {
+        env::var("RPC_HEADERS")
+            .map(|v| v.split(',').map(|s| s.to_string()).collect())
+            .unwrap_or_default()
+    } else {
+        cli_headers.to_vec()
+    };
+
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!("Invalid RPC header '{}', expected KEY=VALUE", entry).into()),
+        })
+        .collect()
+}
+
+/// How the HTTP transport's client sends requests; reqwest's defaults where unset
+#[derive(Debug, Clone, Default)]
+pub struct HttpOptions {
+    /// Extra headers sent with every request
+    pub headers: Vec<(String, String)>,
+    /// Fail a request that hasn't completed in this long, instead of waiting indefinitely
+    pub timeout: Option<Duration>,
+    /// Idle connections kept open per host for reuse
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept before it's closed
+    pub pool_idle_timeout: Option<Duration>,
+}
+
+/// HTTP transport for `url` with a client built from `options`
+pub fn http_transport(
+    url: &str,
+    options: &HttpOptions,
+) -> Result<Http, Box<dyn std::error::Error>> {
+    let client = http_client(options)?;
+    if !options.headers.is_empty() {
+        let names: Vec<&str> = options
+            .headers
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        eprintln!("Using RPC headers: {}", names.join(", "));
+    }
+    Ok(Http::new_with_client(Url::parse(url)?, client))
+}
+
+/// reqwest client with `options`' timeouts, pool settings and headers
+pub fn http_client(options: &HttpOptions) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(max_idle) = options.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(idle_timeout) = options.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(idle_timeout);
+    }
+    if options.headers.is_empty() {
+        return Ok(builder.build()?);
+    }
+
+    let mut header_map = HeaderMap::new();
+    for (key, value) in &options.headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|e| format!("Invalid RPC header name '{}': {}", key, e))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid value for RPC header '{}': {}", key, e))?;
+        // Header values are usually credentials; keep them out of debug output
+        value.set_sensitive(true);
+        header_map.insert(name, value);
+    }
+    Ok(builder.default_headers(header_map).build()?)
+}
+
+/// Where `load_signer` reads the private key from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    /// One line on stdin
+    Stdin,
+    /// The file or named pipe in PRIVATE_KEY_FILE if set, otherwise PRIVATE_KEY
+    Env,
+}
+
+/// Read and parse the key in `source` without contacting the chain
+pub fn load_wallet(source: KeySource) -> Result<LocalWallet, Box<dyn std::error::Error>> {
+    let private_key = read_private_key(source)?;
+    wallet_from_hex(&private_key)
+}
+
+/// Build a signer from the key in `source`, bound to the provider's chain id
+pub async fn load_signer(
+    provider: RpcProvider,
+    source: KeySource,
+) -> Result<SignerMiddleware<RpcProvider, LocalWallet>, Box<dyn std::error::Error>> {
+    let wallet = load_wallet(source)?;
+    eprintln!("Wallet address: {:?}", wallet.address());
+
+    let chain_id = provider.get_chainid().await?.as_u64();
+    Ok(SignerMiddleware::new(
+        provider,
+        wallet.with_chain_id(chain_id),
+    ))
+}
+
+fn read_private_key(source: KeySource) -> Result<Zeroizing<String>, Box<dyn std::error::Error>> {
+    let mut line = Zeroizing::new(String::new());
+    match source {
+        KeySource::Stdin => {
+            std::io::stdin().read_line(&mut line)?;
+            if line.trim().is_empty() {
+                return Err("No private key on stdin".into());
+            }
+        }
+        KeySource::Env => match env::var("PRIVATE_KEY_FILE") {
+            Ok(path) => {
+                // Only the first line is read, so a named pipe works as well as a file
+                let file = fs::File::open(&path)
+                    .map_err(|e| format!("Failed to open PRIVATE_KEY_FILE {}: {}", path, e))?;
+                BufReader::new(file).read_line(&mut line)?;
+                if line.trim().is_empty() {
+                    return Err(format!("No private key in PRIVATE_KEY_FILE {}", path).into());
+                }
+            }
+            Err(_) => {
+                line.push_str(&Zeroizing::new(env::var("PRIVATE_KEY").map_err(|_| {
+                    "PRIVATE_KEY must be set in .env (or use PRIVATE_KEY_FILE / --key-stdin)"
+                })?));
+            }
+        },
+    }
+    Ok(line)
+}
+
+/// Build one signer per private key in `path`: one hex key per line, skipping blank lines
+/// and `#` comments
+pub async fn load_signers(
+    provider: RpcProvider,
+    path: &Path,
+) -> Result<Vec<SignerMiddleware<RpcProvider, LocalWallet>>, Box<dyn std::error::Error>> {
+    let wallets = load_wallets(path)?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    Ok(wallets
+        .into_iter()
+        .map(|wallet| {
+            eprintln!("Wallet address: {:?}", wallet.address());
+            SignerMiddleware::new(provider.clone(), wallet.with_chain_id(chain_id))
+        })
+        .collect())
+}
+
+/// Parse every key in a keys file (one per line, `#` comments allowed) without contacting
+/// the chain
+pub fn load_wallets(path: &Path) -> Result<Vec<LocalWallet>, Box<dyn std::error::Error>> {
+    // Wiped on drop like the single key; the lines below only borrow from it
+    let content = Zeroizing::new(
+        fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read keys file {}: {}", path.display(), e))?,
+    );
+
+    let mut wallets = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let wallet = wallet_from_hex(line).map_err(|e| {
+            format!(
+                "Invalid private key on line {} of {}: {}",
+                line_number + 1,
+                path.display(),
+                e
+            )
+        })?;
+        wallets.push(wallet);
+    }
+
+    if wallets.is_empty() {
+        return Err(format!("No private keys found in {}", path.display()).into());
+    }
+    Ok(wallets)
+}
+
+fn wallet_from_hex(private_key: &str) -> Result<LocalWallet, Box<dyn std::error::Error>> {
+    // The decoded key is wiped on drop; the wallet keeps its own (zeroizing) copy
+    let key_bytes = Zeroizing::new(hex::decode(private_key.trim().trim_start_matches("0x"))?);
+    let signing_key = SigningKey::from_bytes(key_bytes.as_slice().into())?;
+    Ok(LocalWallet::from(signing_key))
+}
+
+/// Fail unless the provider reports `expected` as its chain id, so a mistyped RPC URL can't
+/// get transactions signed for the wrong chain
+pub async fn check_chain_id(
+    provider: &RpcProvider,
+    expected: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let actual = provider.get_chainid().await?.as_u64();
+    if actual != expected {
+        return Err(format!(
+            "Chain id mismatch: RPC endpoint reports {} but {} was expected. Check --rpc-url / the network subcommand, or pass --expected-chain-id {}",
+            actual, expected, actual
+        )
+        .into());
+    }
+    eprintln!("Chain id: {}", actual);
+    Ok(())
+}
+
+/// Chain ids of local development chains (anvil and hardhat, ganache), where nothing sent
+/// costs real funds
+pub const LOCAL_CHAIN_IDS: [u64; 2] = [31337, 1337];
+
+/// Print `summary` with a "Continue? [y/N]" prompt on stderr and fail unless the answer is
+/// yes. Without a terminal to ask on (CI, piped stdin) this fails too, pointing at --yes
+pub fn confirm_spend(summary: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return Err(format!(
+            "{} Not confirmed: stdin is not a terminal; pass --yes to skip the prompt",
+            summary
+        )
+        .into());
+    }
+    eprint!("{} Continue? [y/N] ", summary);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("Aborted; nothing was sent".into()),
+    }
+}
+
+/// forge binary from the CLI, then FORGE_BIN, then the config file
+pub fn resolve_forge_bin(cli_value: Option<String>, config: &Config) -> Option<String> {
+    cli_value
+        .or_else(|| env::var("FORGE_BIN").ok())
+        .or_else(|| config.forge_bin.clone())
+}
+
+/// Build a forge command from `forge_bin`, then FORGE_BIN, then `forge` on PATH
+pub fn forge_command(forge_bin: Option<&str>) -> Command {
+    let bin = forge_bin
+        .map(|s| s.to_string())
+        .or_else(|| env::var("FORGE_BIN").ok())
+        .unwrap_or_else(|| "forge".to_string());
+    Command::new(bin)
+}
+
+/// Turn a failure to spawn forge into an error that says how to point at the binary
+pub fn spawn_error(cmd: &Command, e: std::io::Error) -> Box<dyn std::error::Error> {
+    let program = cmd.get_program().to_string_lossy();
+    if e.kind() == std::io::ErrorKind::NotFound {
+        format!(
+            "forge not found (tried '{}'). Install Foundry or set FORGE_BIN / --forge-bin to the forge binary",
+            program
+        )
+        .into()
+    } else {
+        format!("Failed to run {}: {}", program, e).into()
+    }
+}
@@ -0,0 +1,26 @@
+//! Progress output for binaries with a --json mode, where stdout carries only the JSON result
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Set by --json: from then on `info!` writes to stderr instead of stdout
+pub fn set_json_output(json: bool) {
+    JSON_OUTPUT.store(json, Ordering::Relaxed);
+}
+
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Progress output: stdout normally, stderr with --json
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::output::json_output() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
@@ -0,0 +1,143 @@
+use crate::HttpOptions;
+use async_trait::async_trait;
+use ethers::{
+    providers::{Http, JsonRpcClient, Provider, ProviderError, PubsubClient, Ws},
+    types::U256,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+/// Provider over whichever transport the RPC URL names
+pub type RpcProvider = Provider<Transport>;
+
+/// HTTP or WebSocket JSON-RPC transport, picked at runtime from the URL scheme
+#[derive(Debug, Clone)]
+pub enum Transport {
+    Http(Http),
+    Ws(Ws),
+}
+
+impl Transport {
+    /// Whether new-block subscriptions are available
+    pub fn is_ws(&self) -> bool {
+        matches!(self, Transport::Ws(_))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for Transport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Transport::Http(http) => http.request(method, params).await.map_err(Into::into),
+            Transport::Ws(ws) => ws.request(method, params).await.map_err(Into::into),
+        }
+    }
+}
+
+impl PubsubClient for Transport {
+    type NotificationStream = <Ws as PubsubClient>::NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, ProviderError> {
+        match self {
+            Transport::Ws(ws) => ws.subscribe(id).map_err(Into::into),
+            Transport::Http(_) => Err(ProviderError::CustomError(
+                "Subscriptions need a ws:// or wss:// RPC URL".to_string(),
+            )),
+        }
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), ProviderError> {
+        match self {
+            Transport::Ws(ws) => ws.unsubscribe(id).map_err(Into::into),
+            Transport::Http(_) => Ok(()),
+        }
+    }
+}
+
+/// Whether `url` is a WebSocket endpoint
+pub fn is_ws_url(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// Connect to `url` over WebSocket for ws:// and wss:// URLs, otherwise over HTTP with a
+/// client built from `options`
+pub async fn connect(
+    url: &str,
+    options: &HttpOptions,
+) -> Result<RpcProvider, Box<dyn std::error::Error>> {
+    if !is_ws_url(url) {
+        return Ok(Provider::new(Transport::Http(crate::http_transport(
+            url, options,
+        )?)));
+    }
+
+    if options.timeout.is_some() {
+        eprintln!("⚠️  The HTTP timeout doesn't apply to WebSocket connections");
+    }
+    if !options.headers.is_empty() {
+        return Err("RPC headers are only sent over HTTP; use an http(s):// URL with them".into());
+    }
+    let ws = Ws::connect(url)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    eprintln!("Using WebSocket transport; confirmations follow new-block notifications");
+    Ok(Provider::new(Transport::Ws(ws)))
+}
+
+/// Send `requests` (method and params) to `url` as one JSON-RPC batch, with a client built
+/// from `options`. Each request gets its own result or error message, in request order; one
+/// the response leaves out is an error too, so callers can retry just the failed ones.
+pub async fn batch_request(
+    url: &str,
+    options: &HttpOptions,
+    requests: &[(&str, Value)],
+) -> Result<Vec<Result<Value, String>>, Box<dyn std::error::Error>> {
+    let batch: Vec<Value> = requests
+        .iter()
+        .enumerate()
+        .map(|(id, (method, params))| {
+            json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+        })
+        .collect();
+    let response = crate::http_client(options)?
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(&batch)?)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    // Endpoints without batch support answer with a single error object instead of a list
+    let responses: Vec<Value> = match serde_json::from_slice(&response)? {
+        Value::Array(responses) => responses,
+        other => return Err(format!("RPC endpoint {} rejected the batch: {}", url, other).into()),
+    };
+    let mut results: Vec<Result<Value, String>> = requests
+        .iter()
+        .map(|_| Err("missing from the batch response".to_string()))
+        .collect();
+    for response in responses {
+        let Some(slot) = response
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| results.get_mut(id as usize))
+        else {
+            continue;
+        };
+        *slot = match (response.get("result"), response.get("error")) {
+            (_, Some(error)) => Err(error.to_string()),
+            (Some(result), None) => Ok(result.clone()),
+            (None, None) => Err("response has neither result nor error".to_string()),
+        };
+    }
+    Ok(results)
+}
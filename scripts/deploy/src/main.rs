@@ -1,10 +1,46 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use common::deployment::{
+    broadcast_transactions, deployed_contracts, deployment_addresses, deployment_key,
+    latest_broadcast_file, latest_dry_run_file, simulated_deployment, DEPLOYED_CONTRACTS,
+    DEPLOYMENT_ADDRESSES_FILE, DEPLOY_SCRIPT,
+};
+use common::output::set_json_output;
+use common::{
+    confirm_spend, forge_command, info, load_env_file, read_json_file, resolve_forge_bin,
+    resolve_rpc_url, spawn_error, Config, Network, LOCAL_CHAIN_IDS,
+};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::SystemTime;
+
+/// Periodic column contracts live in the evm-verifier-columns library
+const PERIODIC_COLUMNS_DIR: &str = "lib/evm-verifier-columns";
+
+const BASE_SEPOLIA_BLOCKSCOUT_URL: &str = "https://base-sepolia.blockscout.com/api/";
 
 #[derive(Parser)]
-#[command(name = "deploy")]
+#[command(name = "deploy", version = env!("BUILD_VERSION"))]
 #[command(about = "Deploy STARK verifier contracts to Ethereum networks")]
 struct Cli {
+    /// Path to a playground.toml config file (defaults to ./playground.toml if present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Load env vars from this dotenv file (e.g. .env.staging) before reading any, over
+    /// those already set in the shell
+    #[arg(long, global = true, value_name = "PATH")]
+    env_file: Option<PathBuf>,
+
+    /// Path to the forge binary (overrides FORGE_BIN, defaults to `forge` on PATH)
+    #[arg(long, global = true)]
+    forge_bin: Option<String>,
+
+    /// RPC URL for the target network (overrides the network's env var)
+    #[arg(short, long, global = true)]
+    rpc_url: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -12,55 +48,492 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Deploy to Sepolia testnet
-    Sepolia {
-        /// Dry run (simulate without broadcasting)
-        #[arg(long)]
-        dry: bool,
-    },
+    Sepolia(DeployArgs),
     /// Deploy to Base Sepolia testnet
-    BaseSepolia {
-        /// Dry run (simulate without broadcasting)
-        #[arg(long)]
-        dry: bool,
+    BaseSepolia(DeployArgs),
+    /// Deploy to any network, reading the RPC URL from --rpc-url or an env var
+    Custom {
+        /// Environment variable holding the RPC URL
+        #[arg(long, default_value = "RPC_URL")]
+        rpc_env: String,
+
+        #[command(flatten)]
+        args: DeployArgs,
     },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+#[derive(Args)]
+struct DeployArgs {
+    /// Dry run (simulate without broadcasting)
+    #[arg(long)]
+    dry: bool,
 
-    let rpc_url = match &cli.command {
-        Commands::Sepolia { .. } => {
-            std::env::var("SEPOLIA_RPC_URL").expect("SEPOLIA_RPC_URL must be set in .env")
-        }
-        Commands::BaseSepolia { .. } => {
-            std::env::var("BASE_SEPOLIA_RPC_URL").expect("BASE_SEPOLIA_RPC_URL must be set in .env")
+    /// Simulate the deployment and print the addresses the contracts would be deployed to
+    #[arg(long, conflicts_with_all = ["dry", "resume", "verify", "json"])]
+    predict: bool,
+
+    /// Resume a partially broadcast deployment instead of starting over
+    #[arg(long, conflicts_with = "dry")]
+    resume: bool,
+
+    /// Gas price forwarded to `forge script --gas-price`, in wei
+    #[arg(long, value_name = "WEI")]
+    gas_price: Option<u128>,
+
+    /// Priority fee forwarded to `forge script --priority-gas-price`, in wei
+    #[arg(long, value_name = "WEI")]
+    priority_gas_price: Option<u128>,
+
+    /// Gas price for broadcast transactions, forwarded to `forge script --with-gas-price`, in wei
+    #[arg(long, value_name = "WEI")]
+    with_gas_price: Option<u128>,
+
+    /// Verify the deployed contracts on the block explorer after broadcasting
+    #[arg(long, conflicts_with = "dry")]
+    verify: bool,
+
+    /// Custom block explorer API endpoint for --verify
+    #[arg(long, requires = "verify")]
+    verifier_url: Option<String>,
+
+    /// Print `{network, chainId, contracts, txHashes}` as JSON on stdout once deployed; all
+    /// other output goes to stderr
+    #[arg(long, conflicts_with = "dry")]
+    json: bool,
+
+    /// Broadcast without asking first; otherwise a deployment to a non-local chain is
+    /// simulated and its transaction count and estimated cost confirmed on the terminal
+    #[arg(short, long, conflicts_with_all = ["dry", "predict"])]
+    yes: bool,
+}
+
+impl Commands {
+    fn args(&self) -> &DeployArgs {
+        match self {
+            Commands::Sepolia(args) | Commands::BaseSepolia(args) => args,
+            Commands::Custom { args, .. } => args,
         }
-    };
+    }
 
-    let is_dry =
-        matches!(&cli.command, Commands::Sepolia { dry } | Commands::BaseSepolia { dry } if *dry);
+    fn network(&self) -> Network {
+        match self {
+            Commands::Sepolia(_) => Network::Sepolia,
+            Commands::BaseSepolia(_) => Network::BaseSepolia,
+            Commands::Custom { rpc_env, .. } => Network::Custom {
+                rpc_env: rpc_env.clone(),
+            },
+        }
+    }
+}
 
-    let mut cmd = Command::new("forge");
+/// `forge script` for the deploy script against `rpc_url`, with the fee flags given on the CLI
+/// or in playground.toml; `--broadcast` is left to the caller
+fn script_command(cli: &Cli, config: &Config, rpc_url: &str) -> Command {
+    let mut cmd = forge_command(cli.forge_bin.as_deref());
     cmd.arg("script")
-        .arg("script/Deploy.s.sol:DeployScript")
+        .arg(DEPLOY_SCRIPT)
         .arg("--rpc-url")
-        .arg(&rpc_url)
+        .arg(rpc_url)
         .arg("-vvvv");
 
+    // Fee flags are only passed when given so forge's defaults apply otherwise
+    let args = cli.command.args();
+    for (flag, value) in [
+        ("--gas-price", args.gas_price.or(config.gas.gas_price)),
+        (
+            "--priority-gas-price",
+            args.priority_gas_price.or(config.gas.priority_gas_price),
+        ),
+        (
+            "--with-gas-price",
+            args.with_gas_price.or(config.gas.with_gas_price),
+        ),
+    ] {
+        if let Some(value) = value {
+            cmd.arg(flag).arg(value.to_string());
+        }
+    }
+    cmd
+}
+
+/// The dry-run artifact forge wrote since `started`; an older one may be for other code
+fn fresh_dry_run_file(started: SystemTime) -> Option<PathBuf> {
+    latest_dry_run_file().filter(|path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified >= started)
+    })
+}
+
+/// Ask before broadcasting to a non-local chain: simulate the deployment first and show how
+/// many transactions it sends, from which account and what forge estimates it costs. A
+/// resumed deployment is asked about with what its last broadcast records.
+fn confirm_deployment(
+    cli: &Cli,
+    config: &Config,
+    rpc_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let network = cli.command.network();
+    if cli.command.args().resume {
+        let chain_id = match latest_broadcast_file() {
+            Some(path) => broadcast_transactions(&path)?.0,
+            None => None,
+        };
+        if chain_id.is_some_and(|id| LOCAL_CHAIN_IDS.contains(&id)) {
+            return Ok(());
+        }
+        let target = match chain_id {
+            Some(id) => network.describe(id),
+            None => rpc_url.to_string(),
+        };
+        return confirm_spend(&format!(
+            "About to resume the recorded deployment on {}.",
+            target
+        ));
+    }
+
+    info!("🔍 Simulating deployment to estimate its cost...");
+    let mut cmd = script_command(cli, config, rpc_url);
+    let started = SystemTime::now();
+    let output = cmd.output().map_err(|e| spawn_error(&cmd, e))?;
+    if !output.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&output.stdout));
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        return Err("Simulation failed".into());
+    }
+    let path = fresh_dry_run_file(started).ok_or(
+        "The simulation wrote no dry-run file under broadcast/ to confirm from; pass --yes to deploy without confirming",
+    )?;
+    let simulation = simulated_deployment(&path)?;
+    if simulation
+        .chain_id
+        .is_some_and(|id| LOCAL_CHAIN_IDS.contains(&id))
+    {
+        return Ok(());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    confirm_spend(&format!(
+        "About to send {} transactions from {} on {}, estimated cost {}.",
+        simulation.transactions,
+        simulation
+            .from
+            .as_deref()
+            .unwrap_or("the PRIVATE_KEY account"),
+        match simulation.chain_id {
+            Some(id) => network.describe(id),
+            None => rpc_url.to_string(),
+        },
+        estimated_amount(&stdout).unwrap_or("unknown")
+    ))
+}
+
+/// The "Estimated amount required: 0.01 ETH" total from forge's simulation output
+fn estimated_amount(output: &str) -> Option<&str> {
+    output.lines().find_map(|line| {
+        line.split_once("Estimated amount required:")
+            .map(|(_, amount)| amount.trim())
+    })
+}
+
+/// Check that forge runs and the deploy script exists before spawning `forge script`
+fn preflight(forge_bin: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = forge_command(forge_bin);
+    cmd.arg("--version");
+    let output = cmd.output().map_err(|e| spawn_error(&cmd, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`{} --version` failed. Reinstall Foundry with `foundryup` or point FORGE_BIN / --forge-bin at a working forge",
+            cmd.get_program().to_string_lossy()
+        )
+        .into());
+    }
+    let version = String::from_utf8_lossy(&output.stdout);
+    info!("  {}", version.lines().next().unwrap_or("forge").trim());
+
+    let script_file = DEPLOY_SCRIPT.split(':').next().unwrap_or(DEPLOY_SCRIPT);
+    if !Path::new(script_file).is_file() {
+        return Err(format!(
+            "Deploy script {} not found (current directory: {:?}). Run the deploy runner from the repository root",
+            script_file,
+            std::env::current_dir()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Merge contract addresses from a forge broadcast into deployment-addresses.json
+fn update_deployment_addresses(
+    contracts: &[(String, String)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Keep whatever is already in the file and overwrite only what was just deployed; a file
+    // that doesn't parse is an error rather than something to overwrite
+    let mut addresses: Map<String, Value> = match read_json_file(DEPLOYMENT_ADDRESSES_FILE) {
+        Ok(addresses) => addresses,
+        Err(e) if e.is_not_found() => Map::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    for (name, address) in contracts {
+        let key = deployment_key(name);
+        info!("  {}: {}", name, address);
+        addresses.insert(key.to_string(), Value::String(address.clone()));
+    }
+
+    fs::write(
+        DEPLOYMENT_ADDRESSES_FILE,
+        serde_json::to_string_pretty(&addresses)? + "\n",
+    )?;
+    info!(
+        "📝 Saved deployed addresses to {}",
+        DEPLOYMENT_ADDRESSES_FILE
+    );
+
+    Ok(())
+}
+
+/// `path:Name` identifier that forge verify-contract uses to locate a contract's source
+fn contract_identifier(name: &str) -> String {
+    match DEPLOYED_CONTRACTS
+        .iter()
+        .find(|(contract, _, _)| *contract == name)
+    {
+        Some((_, _, source)) => format!("{}:{}", source, name),
+        None => format!("{}/{}.sol:{}", PERIODIC_COLUMNS_DIR, name, name),
+    }
+}
+
+/// Run `forge verify-contract` for each deployed contract and report per-contract status
+fn verify_contracts(
+    cli: &Cli,
+    rpc_url: &str,
+    contracts: &[(String, String)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let args = cli.command.args();
+    info!(
+        "\n🔎 Verifying {} contracts on {}...",
+        contracts.len(),
+        cli.command.network().chain().unwrap_or(rpc_url)
+    );
+
+    let mut failed = Vec::new();
+    for (name, address) in contracts {
+        let mut cmd = forge_command(cli.forge_bin.as_deref());
+        cmd.arg("verify-contract")
+            .arg(address)
+            .arg(contract_identifier(name))
+            // Constructor arguments are recovered from the creation transaction
+            .arg("--guess-constructor-args")
+            .arg("--rpc-url")
+            .arg(rpc_url)
+            .arg("--watch");
+        if let Some(chain) = cli.command.network().chain() {
+            cmd.arg("--chain").arg(chain);
+        }
+
+        match (&cli.command, &args.verifier_url) {
+            (Commands::BaseSepolia(_), url) => {
+                cmd.arg("--verifier")
+                    .arg("blockscout")
+                    .arg("--verifier-url")
+                    .arg(url.as_deref().unwrap_or(BASE_SEPOLIA_BLOCKSCOUT_URL));
+            }
+            (Commands::Sepolia(_), url) => {
+                let api_key = std::env::var("ETHERSCAN_API_KEY")
+                    .map_err(|_| "ETHERSCAN_API_KEY must be set in .env to verify on Etherscan")?;
+                cmd.arg("--etherscan-api-key").arg(api_key);
+                if let Some(url) = url {
+                    cmd.arg("--verifier-url").arg(url);
+                }
+            }
+            (Commands::Custom { .. }, url) => {
+                if let Ok(api_key) = std::env::var("ETHERSCAN_API_KEY") {
+                    cmd.arg("--etherscan-api-key").arg(api_key);
+                }
+                if let Some(url) = url {
+                    cmd.arg("--verifier-url").arg(url);
+                }
+            }
+        }
+
+        let output = cmd.output().map_err(|e| spawn_error(&cmd, e))?;
+        if output.status.success() {
+            info!("  ✅ {} ({})", name, address);
+        } else {
+            info!("  ❌ {} ({})", name, address);
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            failed.push(name.as_str());
+        }
+    }
+
+    if failed.is_empty() {
+        info!("✅ All contracts verified");
+        Ok(())
+    } else {
+        Err(format!("Verification failed for: {}", failed.join(", ")).into())
+    }
+}
+
+/// Run the simulation in `cmd` and print the address each contract would be deployed to,
+/// from forge's dry-run artifact or, failing that, the `new Name@0x...` lines in its trace
+fn predict_addresses(cmd: &mut Command) -> Result<(), Box<dyn std::error::Error>> {
+    let started = SystemTime::now();
+    let output = cmd.output().map_err(|e| spawn_error(cmd, e))?;
+    if !output.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&output.stdout));
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        return Err("Simulation failed".into());
+    }
+
+    let contracts = match fresh_dry_run_file(started) {
+        Some(path) => {
+            info!("📄 Reading simulation {}", path.display());
+            deployed_contracts(&path)?
+        }
+        None => contracts_from_trace(&String::from_utf8_lossy(&output.stdout)),
+    };
+    if contracts.is_empty() {
+        return Err("No contract creations found in the simulation".into());
+    }
+
+    info!("✅ Predicted addresses:");
+    for (name, address) in &contracts {
+        info!("  {}: {}", name, address);
+    }
+    info!(
+        "\n{} would contain:\n{}",
+        DEPLOYMENT_ADDRESSES_FILE,
+        serde_json::to_string_pretty(&deployment_addresses(&contracts))?
+    );
+    info!("⚠️  CREATE addresses depend on the deployer's nonce; sending any other transaction from it first changes them");
+    Ok(())
+}
+
+/// `(contractName, contractAddress)` from the `new Name@0x...` creation lines in a -vvvv trace
+fn contracts_from_trace(trace: &str) -> Vec<(String, String)> {
+    let mut contracts: Vec<(String, String)> = Vec::new();
+    for line in trace.lines() {
+        let Some((_, created)) = line.split_once("new ") else {
+            continue;
+        };
+        let Some((name, rest)) = created.split_once('@') else {
+            continue;
+        };
+        let name = name.trim();
+        let address: String = rest.chars().take(42).collect();
+        let is_address = address.len() == 42
+            && address.starts_with("0x")
+            && address[2..].chars().all(|c| c.is_ascii_hexdigit());
+        if is_address && !name.is_empty() && !contracts.iter().any(|(n, _)| n == name) {
+            contracts.push((name.to_string(), address));
+        }
+    }
+    contracts
+}
+
+/// The deployment result as one JSON object on stdout, for scripts
+fn print_json_result(
+    cli: &Cli,
+    broadcast_path: &Path,
+    contracts: &[(String, String)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (chain_id, tx_hashes) = broadcast_transactions(broadcast_path)?;
+    let network = cli.command.network();
+    let contracts: Map<String, Value> = contracts
+        .iter()
+        .map(|(name, address)| (name.clone(), Value::String(address.clone())))
+        .collect();
+
+    let result = serde_json::json!({
+        "network": network.chain().unwrap_or("custom"),
+        "chainId": chain_id.or_else(|| network.chain_id()),
+        "contracts": contracts,
+        "txHashes": tx_hashes,
+    });
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Cli::parse();
+    if let Some(env_file) = &cli.env_file {
+        load_env_file(env_file)?;
+    }
+    let config = Config::load(cli.config.as_deref())?;
+    cli.forge_bin = resolve_forge_bin(cli.forge_bin.take(), &config);
+
+    // Explicit --rpc-url wins over the network's env var, then playground.toml
+    let rpc_url = resolve_rpc_url(&cli.command.network(), cli.rpc_url.as_deref(), &config)?;
+
+    let is_dry = cli.command.args().dry || cli.command.args().predict;
+    let json = cli.command.args().json;
+    set_json_output(json);
+
+    preflight(cli.forge_bin.as_deref())?;
+
+    if !is_dry && !cli.command.args().yes {
+        confirm_deployment(&cli, &config, &rpc_url)?;
+    }
+
+    let mut cmd = script_command(&cli, &config, &rpc_url);
+    if json {
+        // Keep stdout for the JSON result
+        cmd.stdout(std::io::stderr());
+    }
+
     if !is_dry {
         cmd.arg("--broadcast");
-        println!("🚀 Deploying to network...");
+        if cli.command.args().resume {
+            // forge picks up the transactions recorded in broadcast/ for this script and sender
+            cmd.arg("--resume");
+            info!("🔁 Resuming previous deployment from broadcast/ (same script and sender required)...");
+        } else {
+            info!("🚀 Deploying to network...");
+        }
+    } else if cli.command.args().predict {
+        info!("🔮 Simulating deployment to predict contract addresses...");
     } else {
-        println!("🔍 Simulating deployment (dry run)...");
+        info!("🔍 Simulating deployment (dry run)...");
+    }
+
+    if cli.command.args().predict {
+        return predict_addresses(&mut cmd);
     }
 
-    let status = cmd.status()?;
+    let status = cmd.status().map_err(|e| spawn_error(&cmd, e))?;
 
     if status.success() {
         if !is_dry {
-            println!("✅ Deployment complete!");
+            info!("✅ Deployment complete!");
+            match latest_broadcast_file() {
+                Some(path) => {
+                    info!("📄 Reading broadcast {}", path.display());
+                    let contracts = deployed_contracts(&path)?;
+                    update_deployment_addresses(&contracts)?;
+                    if cli.command.args().verify {
+                        verify_contracts(&cli, &rpc_url, &contracts)?;
+                    }
+                    if json {
+                        print_json_result(&cli, &path, &contracts)?;
+                    }
+                }
+                None => {
+                    eprintln!(
+                        "⚠️  Broadcast file not found under broadcast/; {} was not updated",
+                        DEPLOYMENT_ADDRESSES_FILE
+                    );
+                    if cli.command.args().verify {
+                        return Err("Cannot verify contracts without a broadcast file".into());
+                    }
+                    if json {
+                        return Err("Cannot print the JSON result without a broadcast file".into());
+                    }
+                }
+            }
         } else {
-            println!("✅ Simulation complete!");
+            info!("✅ Simulation complete!");
         }
         Ok(())
     } else {
@@ -0,0 +1,39 @@
+//! Annotated proofs the benches run on, shared by every bench target.
+
+use std::path::PathBuf;
+
+/// Fixture name, env var overriding its path, and default path relative to the repo root
+pub const FIXTURES: [(&str, &str, &str); 2] = [
+    (
+        "small",
+        "SPLIT_BENCH_SMALL",
+        "examples/fibonacci-bootloader/annotated_proof.json",
+    ),
+    (
+        "large",
+        "SPLIT_BENCH_LARGE",
+        "examples/factorial-bootloader/annotated_proof.json",
+    ),
+];
+
+/// Path of a fixture, panicking if it doesn't exist: a bench that quietly skips its inputs
+/// reports numbers for nothing
+pub fn fixture_path(name: &str, env_var: &str, default: &str) -> PathBuf {
+    let path = std::env::var(env_var)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../..")
+                .join(default)
+        });
+    if !path.is_file() {
+        panic!(
+            "{} fixture {} not found: generate the annotated proof with the prover \
+             (the examples ship only input.json) or point {} at one",
+            name,
+            path.display(),
+            env_var
+        );
+    }
+    path
+}
@@ -0,0 +1,90 @@
+//! Peak heap use and time of parsing an annotated proof from a `String` (`read_to_string` then
+//! `serde_json::from_str`) against streaming it with `serde_json::from_reader`, which is
+//! what `prepare_verifier_input` does.
+//!
+//! Run with `cargo bench -p prepare-input --bench parse_memory`. Uses the same fixtures and
+//! SPLIT_BENCH_SMALL / SPLIT_BENCH_LARGE overrides as the split benchmark.
+
+use prepare_input::{open_input, read_input_to_string};
+use stark_evm_adapter::annotated_proof::AnnotatedProof;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+mod fixtures;
+
+use fixtures::{fixture_path, FIXTURES};
+
+/// System allocator that keeps the current and peak number of allocated bytes
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes allocated at the high-water mark of `f`, above what was allocated before it ran,
+/// and how long it took
+fn measure<T>(f: impl FnOnce() -> T) -> (usize, Duration) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let start = Instant::now();
+    drop(f());
+    let elapsed = start.elapsed();
+    (PEAK.load(Ordering::Relaxed) - before, elapsed)
+}
+
+fn from_string(path: &Path) -> AnnotatedProof {
+    let content = read_input_to_string(path).unwrap();
+    serde_json::from_str(&content).unwrap()
+}
+
+fn from_reader(path: &Path) -> AnnotatedProof {
+    serde_json::from_reader(open_input(path).unwrap()).unwrap()
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn main() {
+    println!(
+        "{:<8} {:>10} {:>14} {:>14} {:>17} {:>17}",
+        "fixture", "file MiB", "from_str MiB", "from_str ms", "from_reader MiB", "from_reader ms"
+    );
+    for (name, env_var, default) in FIXTURES {
+        let path = fixture_path(name, env_var, default);
+        let size = std::fs::metadata(&path)
+            .unwrap_or_else(|e| panic!("Failed to stat {}: {}", path.display(), e))
+            .len() as usize;
+        let (string_peak, string_time) = measure(|| from_string(&path));
+        let (reader_peak, reader_time) = measure(|| from_reader(&path));
+        println!(
+            "{:<8} {:>10.1} {:>14.1} {:>14} {:>17.1} {:>17}",
+            name,
+            mib(size),
+            mib(string_peak),
+            string_time.as_millis(),
+            mib(reader_peak),
+            reader_time.as_millis()
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Times `split_fri_merkle_statements` on real annotated proofs, in proof bytes per second.
+//!
+//! Run with `cargo bench -p prepare-input`. The fixtures are the annotated proofs the prover
+//! writes next to the examples, which aren't committed: generate them first, or point
+//! SPLIT_BENCH_SMALL / SPLIT_BENCH_LARGE at other proofs. A missing fixture fails the bench.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use prepare_input::read_input_to_string;
+use stark_evm_adapter::{
+    annotated_proof::AnnotatedProof, annotation_parser::split_fri_merkle_statements,
+};
+
+mod fixtures;
+
+use fixtures::{fixture_path, FIXTURES};
+
+fn split(c: &mut Criterion) {
+    let mut group = c.benchmark_group("split_fri_merkle_statements");
+    // Splitting a large proof is slow enough that criterion's default 100 samples drag on
+    group.sample_size(10);
+
+    for (name, env_var, default) in FIXTURES {
+        let path = fixture_path(name, env_var, default);
+        let content = read_input_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        let annotated_proof: AnnotatedProof = serde_json::from_str(&content)
+            .unwrap_or_else(|e| panic!("Failed to parse {}: {}", path.display(), e));
+
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_function(name, |b| {
+            // Cloning isn't timed: only the split itself is
+            b.iter_batched(
+                || annotated_proof.clone(),
+                |proof| split_fri_merkle_statements(proof).expect("Failed to split proof"),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, split);
+criterion_main!(benches);
@@ -0,0 +1 @@
+include!("../build_info.rs");
@@ -1,12 +1,20 @@
-use ethers::types::U256;
+use common::read_json_file;
+pub use common::{open_input, read_input_to_string};
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{AbiParser, Token};
+use ethers::types::{Bytes, U256};
 use num_bigint::BigInt;
 use num_traits::{Num, One, Zero};
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
-use std::collections::HashMap;
-use std::fs;
-use std::str::FromStr;
+use stark_evm_adapter::{
+    annotated_proof::AnnotatedProof as SplitAnnotatedProof,
+    annotation_parser::split_fri_merkle_statements,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
 /// Prime field constant for Cairo
 const K_MODULUS_STR: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
@@ -31,7 +39,7 @@ pub struct PublicMemory {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct PublicInput {
     layout: String,
-    memory_segments: HashMap<String, MemorySegment>,
+    memory_segments: BTreeMap<String, MemorySegment>,
     n_steps: u64,
     public_memory: Vec<PublicMemory>,
     rc_min: i64,
@@ -65,26 +73,77 @@ struct FriParams {
     fri_step_list: Vec<u32>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 struct MemoryPageRegular {
     #[serde(with = "hex_vec")]
+    #[schemars(with = "Vec<String>")]
     memory_pairs: Vec<BigInt>, // [addr0, value0, addr1, value1, ...]
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 struct MemoryPageContinuous {
     #[serde(with = "hex")]
+    #[schemars(with = "String")]
     start_addr: BigInt,
     #[serde(with = "hex_vec")]
+    #[schemars(with = "Vec<String>")]
     values: Vec<BigInt>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct MemoryPageFacts {
     regular_page: Option<MemoryPageRegular>,
     continuous_pages: Vec<MemoryPageContinuous>,
 }
 
+impl MemoryPageFacts {
+    /// Number of memory pages, counting the regular page if present
+    pub fn page_count(&self) -> usize {
+        usize::from(self.regular_page.is_some()) + self.continuous_pages.len()
+    }
+
+    /// Number of continuous pages, each registered in its own transaction
+    pub fn continuous_page_count(&self) -> usize {
+        self.continuous_pages.len()
+    }
+}
+
+/// Transactions the split verify flow sends before the main proof
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxCounts {
+    pub merkle_statements: usize,
+    pub fri_statements: usize,
+    pub continuous_pages: usize,
+}
+
+impl TxCounts {
+    /// Total transaction count including the final verifyProofAndRegister call
+    pub fn total(&self) -> usize {
+        self.merkle_statements + self.fri_statements + self.continuous_pages + 1
+    }
+}
+
+/// What an annotated proof contains, for `prepare-input inspect`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofSummary {
+    pub annotations: usize,
+    /// Bytes of proof_hex
+    pub proof_bytes: usize,
+    pub layout: String,
+    pub n_steps: u64,
+    /// Trace commitments the annotations record (original, interaction and composition)
+    pub trace_commitments: usize,
+    /// FRI layer commitments the annotations record
+    pub fri_commitments: usize,
+    pub fri_step_list: Vec<u32>,
+    pub n_queries: u32,
+    pub last_layer_degree_bound: u32,
+    /// Public memory cells on each page, by page
+    pub memory_pages: BTreeMap<u32, usize>,
+    /// `(begin_addr, stop_ptr)` of each public input memory segment, by name
+    pub segments: BTreeMap<String, (u64, u64)>,
+}
+
 /// Fact topology for GPS verifier task metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FactTopology {
@@ -98,27 +157,347 @@ pub struct FactTopologiesFile {
     pub fact_topologies: Vec<FactTopology>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Everything the verify script needs in one file, produced by `prepare-input bundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofBundle {
+    /// Contents of annotated_proof.json
+    pub annotated_proof: serde_json::Value,
+    /// Prepared verifier input, in the input.json layout
+    pub input: VerifierInput,
+    /// The `fact_topologies` array from fact_topologies.json
+    pub fact_topologies: Vec<FactTopology>,
+    /// Contents of deployment-addresses.json, if it was included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_addresses: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct VerifierInput {
     #[serde(with = "u256_vec")]
+    #[schemars(with = "Vec<String>")]
     pub proof_params: Vec<U256>,
     #[serde(with = "u256_vec")]
+    #[schemars(with = "Vec<String>")]
     pub proof: Vec<U256>,
     #[serde(with = "u256_vec")]
+    #[schemars(with = "Vec<String>")]
     pub public_input: Vec<U256>,
     #[serde(with = "u256_hex")]
+    #[schemars(with = "String")]
     pub z: U256,
     #[serde(with = "u256_hex")]
+    #[schemars(with = "String")]
     pub alpha: U256,
     pub memory_page_facts: MemoryPageFacts,
-    #[serde(with = "u256_vec")]
+    /// Fact hashes registered by MemoryPageFactRegistry for each continuous page, in page order
+    /// (empty in inputs prepared before they were recorded)
+    #[serde(default)]
+    pub memory_page_fact_hashes: Vec<String>,
+    /// Empty for proofs that don't run under the bootloader
+    #[serde(default, with = "u256_vec")]
+    #[schemars(with = "Vec<String>")]
     pub task_metadata: Vec<U256>,
+    /// `integrity_hash` of the fields above when the file was written, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+    /// Each bootloader task's place in the program output, in task order (empty for proofs
+    /// without tasks)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskInfo>,
+}
+
+/// Where one bootloader task's output sits in the proof's public memory
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct TaskInfo {
+    /// Program hash from the task's output header, as hex
+    pub program_hash: String,
+    /// Address of the first value of the task's output (its size)
+    pub output_start: u64,
+    /// Number of output values, including the size and program hash header
+    pub output_size: usize,
+    /// Public memory pages holding the task's output, ascending
+    pub pages: Vec<u32>,
+}
+
+impl VerifierInput {
+    /// keccak256 over the integer fields in their canonical form: proof_params, proof,
+    /// public_input and task_metadata, each as a 32-byte length followed by 32-byte
+    /// big-endian words, then z and alpha
+    pub fn integrity_hash(&self) -> String {
+        let mut data = Vec::new();
+        for array in [
+            &self.proof_params,
+            &self.proof,
+            &self.public_input,
+            &self.task_metadata,
+        ] {
+            data.extend_from_slice(&u256_to_bytes32(&U256::from(array.len())));
+            for word in array {
+                data.extend_from_slice(&u256_to_bytes32(word));
+            }
+        }
+        data.extend_from_slice(&u256_to_bytes32(&self.z));
+        data.extend_from_slice(&u256_to_bytes32(&self.alpha));
+        format!("0x{}", ethers::utils::hex::encode(keccak256(&data)))
+    }
+
+    /// Fill in `integrity` from the current contents
+    pub fn with_integrity(mut self) -> VerifierInput {
+        self.integrity = Some(self.integrity_hash());
+        self
+    }
+
+    /// Fail if an embedded `integrity` hash no longer matches the contents; inputs without
+    /// one pass
+    pub fn check_integrity(&self) -> Result<(), String> {
+        match &self.integrity {
+            Some(expected) if !expected.eq_ignore_ascii_case(&self.integrity_hash()) => Err(format!(
+                "Integrity check failed: input records {} but its contents hash to {}; the file is truncated or was edited",
+                expected,
+                self.integrity_hash()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// input.json in the layout test/CpuVerifier.t.sol's `test_VerifyProof` reads, so it can be
+/// dropped straight into `examples/<program>/`.
+///
+/// The test registers the regular page unconditionally, so a proof without one is rejected.
+pub fn forge_test_input(verifier_input: &VerifierInput) -> Result<serde_json::Value, String> {
+    if verifier_input.memory_page_facts.regular_page.is_none() {
+        return Err(
+            "Proof has no regular memory page (page 0), which test_VerifyProof requires".into(),
+        );
+    }
+
+    let mut input = serde_json::to_value(verifier_input).map_err(|e| e.to_string())?;
+    let fields = input
+        .as_object_mut()
+        .ok_or("VerifierInput did not serialize to an object")?;
+    // Checked by the verify script only; the test computes page facts itself
+    fields.remove("memory_page_fact_hashes");
+    fields.remove("integrity");
+    fields.remove("tasks");
+    Ok(input)
+}
+
+/// Serialize a verifier input as CBOR, with integers as big-endian byte strings
+pub fn verifier_input_to_cbor(verifier_input: &VerifierInput) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(verifier_input, &mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Extension of the file inside `path`: `json` for both `input.json` and `input.json.gz`
+fn uncompressed_extension(path: &Path) -> Option<&str> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz" | "zst") => Path::new(path.file_stem()?)
+            .extension()
+            .and_then(|ext| ext.to_str()),
+        ext => ext,
+    }
+}
+
+/// Names other prover toolchains give the top-level input.json fields, by the field they
+/// stand for. Each is also matched case-insensitively, as is the field's own name.
+pub const INPUT_FIELD_ALIASES: &[(&str, &[&str])] = &[
+    (
+        "proof_params",
+        &["proofParams", "proof_parameters", "proofParameters"],
+    ),
+    ("public_input", &["publicInput"]),
+    ("memory_page_facts", &["memoryPageFacts"]),
+    ("memory_page_fact_hashes", &["memoryPageFactHashes"]),
+    ("task_metadata", &["taskMetadata"]),
+];
+
+/// Rename aliased top-level fields of an input.json object to the names `VerifierInput`
+/// reads, returning each `(found, field)` rename. Fails when two spellings of one field are
+/// present, since either could be the one meant.
+pub fn resolve_input_aliases(
+    fields: &mut serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<(String, &'static str)>, String> {
+    let mut renamed = Vec::new();
+    for (field, aliases) in INPUT_FIELD_ALIASES {
+        let matches: Vec<String> = fields
+            .keys()
+            .filter(|key| {
+                key.eq_ignore_ascii_case(field)
+                    || aliases.iter().any(|alias| key.eq_ignore_ascii_case(alias))
+            })
+            .cloned()
+            .collect();
+        match matches.as_slice() {
+            [] => {}
+            [key] if key == field => {}
+            [key] => {
+                let value = fields.remove(key).expect("matched key is present");
+                fields.insert(field.to_string(), value);
+                renamed.push((key.clone(), *field));
+            }
+            keys => {
+                return Err(format!(
+                    "{} is given more than once, as {}",
+                    field,
+                    keys.join(", ")
+                ))
+            }
+        }
+    }
+    Ok(renamed)
+}
+
+/// Read a prepared verifier input: CBOR for a `.cbor` file, JSON otherwise, either of them
+/// optionally compressed. JSON field names may use the spellings in `INPUT_FIELD_ALIASES`.
+pub fn read_verifier_input(path: &Path) -> Result<VerifierInput, String> {
+    let reader =
+        open_input(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if uncompressed_extension(path) == Some("cbor") {
+        return ciborium::from_reader(reader)
+            .map_err(|e| format!("Failed to parse CBOR from {}: {}", path.display(), e));
+    }
+
+    let mut input: serde_json::Value = serde_json::from_reader(reader)
+        .map_err(|e| format!("Failed to parse JSON from {}: {}", path.display(), e))?;
+    if let Some(fields) = input.as_object_mut() {
+        let renamed =
+            resolve_input_aliases(fields).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for (found, field) in renamed {
+            println!("Reading {} field {:?} as {}", path.display(), found, field);
+        }
+    }
+    serde_json::from_value(input)
+        .map_err(|e| format!("Failed to parse JSON from {}: {}", path.display(), e))
+}
+
+/// Name for the input prepared from `annotated_proof_path`, with `extension` (e.g. "json"):
+/// `fibonacci.annotated.json` becomes `fibonacci.input.json`, `fibonacci_annotated_proof.json`
+/// becomes `fibonacci_input.json` and `annotated_proof.json` becomes `input.json`
+pub fn input_file_name(annotated_proof_path: &str, extension: &str) -> String {
+    let file_name = Path::new(annotated_proof_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(annotated_proof_path);
+    let file_name = [".gz", ".zst"]
+        .iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(file_name);
+    let stem = file_name.strip_suffix(".json").unwrap_or(file_name);
+
+    let base = if stem == "annotated_proof" {
+        "input".to_string()
+    } else if let Some(base) = [".annotated_proof", ".annotated"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+    {
+        format!("{}.input", base)
+    } else if let Some(base) = stem.strip_suffix("_annotated_proof") {
+        format!("{}_input", base)
+    } else {
+        format!("{}.input", stem)
+    };
+    format!("{}.{}", base, extension)
+}
+
+/// JSON Schema for input.json, derived from `VerifierInput`
+pub fn verifier_input_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(VerifierInput))
+        .expect("Failed to serialize VerifierInput schema")
+}
+
+/// Check an input.json value against `verifier_input_schema`, returning one message per violation
+pub fn validate_verifier_input(instance: &serde_json::Value) -> Vec<String> {
+    let schema = verifier_input_schema();
+    let compiled =
+        jsonschema::JSONSchema::compile(&schema).expect("VerifierInput schema is not valid");
+
+    match compiled.validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                let path = if path.is_empty() {
+                    "/".to_string()
+                } else {
+                    path
+                };
+                format!("{}: {}", path, e)
+            })
+            .collect(),
+    }
+}
+
+/// Integers as compact big-endian byte strings, for binary formats such as CBOR
+mod binary {
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    /// Serialize big-endian `bytes` with leading zeros stripped
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        serializer.serialize_bytes(&bytes[start..])
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("big-endian integer bytes")
+        }
+
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::new();
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
 }
 
 mod hex_vec {
     use num_bigint::BigInt;
     use serde::ser::SerializeSeq;
-    use serde::Serializer;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    struct Element<'a>(&'a BigInt);
+
+    impl Serialize for Element<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::hex::serialize(self.0, serializer)
+        }
+    }
+
+    struct Owned(BigInt);
+
+    impl<'de> Deserialize<'de> for Owned {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::hex::deserialize(deserializer).map(Owned)
+        }
+    }
 
     pub fn serialize<S>(vec: &[BigInt], serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -126,30 +505,76 @@ mod hex_vec {
     {
         let mut seq = serializer.serialize_seq(Some(vec.len()))?;
         for item in vec {
-            let hex_str = format!("0x{:x}", item);
-            seq.serialize_element(&hex_str)?;
+            seq.serialize_element(&Element(item))?;
         }
         seq.end()
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<BigInt>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Owned>::deserialize(deserializer)?
+            .into_iter()
+            .map(|owned| owned.0)
+            .collect())
+    }
 }
 
+/// Hex strings in JSON, big-endian bytes in binary formats
 mod hex {
-    use num_bigint::BigInt;
-    use serde::Serializer;
+    use num_bigint::{BigInt, Sign};
+    use num_traits::Num;
+    use serde::{Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(val: &BigInt, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            return super::binary::serialize(&val.to_bytes_be().1, serializer);
+        }
         let hex_str = format!("0x{:x}", val);
         serializer.serialize_str(&hex_str)
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BigInt, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            let bytes = super::binary::deserialize(deserializer)?;
+            return Ok(BigInt::from_bytes_be(Sign::Plus, &bytes));
+        }
+        parse(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+
+    pub(super) fn parse(s: &str) -> Result<BigInt, String> {
+        BigInt::from_str_radix(s.trim_start_matches("0x"), 16)
+            .map_err(|e| format!("invalid hex value {:?}: {}", s, e))
+    }
 }
 
 mod u256_vec {
     use ethers::types::U256;
     use serde::ser::SerializeSeq;
-    use serde::Serializer;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    struct Element<'a>(&'a U256);
+
+    impl Serialize for Element<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::u256_hex::serialize(self.0, serializer)
+        }
+    }
+
+    struct Owned(U256);
+
+    impl<'de> Deserialize<'de> for Owned {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::u256_hex::deserialize(deserializer).map(Owned)
+        }
+    }
 
     pub fn serialize<S>(vec: &[U256], serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -157,29 +582,67 @@ mod u256_vec {
     {
         let mut seq = serializer.serialize_seq(Some(vec.len()))?;
         for item in vec {
-            let hex_str = format!("0x{:x}", item);
-            seq.serialize_element(&hex_str)?;
+            seq.serialize_element(&Element(item))?;
         }
         seq.end()
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<U256>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Owned>::deserialize(deserializer)?
+            .into_iter()
+            .map(|owned| owned.0)
+            .collect())
+    }
 }
 
+/// Hex strings in JSON, big-endian bytes in binary formats
 mod u256_hex {
     use ethers::types::U256;
-    use serde::Serializer;
+    use serde::{Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(val: &U256, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            let mut bytes = [0u8; 32];
+            val.to_big_endian(&mut bytes);
+            return super::binary::serialize(&bytes, serializer);
+        }
         let hex_str = format!("0x{:x}", val);
         serializer.serialize_str(&hex_str)
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            let bytes = super::binary::deserialize(deserializer)?;
+            if bytes.len() > 32 {
+                return Err(serde::de::Error::custom(format!(
+                    "uint256 value is {} bytes long",
+                    bytes.len()
+                )));
+            }
+            return Ok(U256::from_big_endian(&bytes));
+        }
+        parse(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+
+    pub(super) fn parse(s: &str) -> Result<U256, String> {
+        U256::from_str_radix(s.trim_start_matches("0x"), 16)
+            .map_err(|e| format!("invalid uint256 value {:?}: {}", s, e))
+    }
 }
 
 fn parse_annotated_proof(path: &str) -> AnnotatedProof {
-    let content = fs::read_to_string(path).expect(&format!("Failed to read file: {}", path));
-    serde_json::from_str(&content).expect(&format!("Failed to parse JSON from: {}", path))
+    // read_json_file parses straight from a buffered reader, so the (possibly hundreds of
+    // MB) file is never held in memory as a String alongside the parsed proof
+    read_json_file(path).unwrap_or_else(|e| panic!("{}", e))
 }
 
 fn extract_interaction_elements(annotations: &[String]) -> (BigInt, BigInt) {
@@ -264,6 +727,595 @@ fn keccak256(data: &[u8]) -> [u8; 32] {
     hasher.finalize().into()
 }
 
+/// The verifier's configuration that `proof_params` encodes, by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProofParamsView {
+    pub n_queries: u64,
+    /// log2 of the blowup factor
+    pub log_n_cosets: u64,
+    pub proof_of_work_bits: u64,
+    /// proof_params carries the bound as its log2, rounded up
+    pub last_layer_degree_bound: u64,
+    /// FRI step sizes, the first layer's included
+    pub fri_step_sizes: Vec<u64>,
+}
+
+/// Name the words of `params`, laid out as [n_queries, log_n_cosets, pow_bits, log_last_layer,
+/// n_fri_steps, steps...]
+pub fn decode_proof_params(params: &[U256]) -> Result<ProofParamsView, String> {
+    let word = |index: usize, name: &str| -> Result<u64, String> {
+        let value = params.get(index).ok_or_else(|| {
+            format!(
+                "proof_params has {} words; {} would be at index {}",
+                params.len(),
+                name,
+                index
+            )
+        })?;
+        if value.bits() > 32 {
+            return Err(format!(
+                "proof_params[{}] ({}) is {}, too large",
+                index, name, value
+            ));
+        }
+        Ok(value.as_u64())
+    };
+    let log_last_layer = word(3, "log_last_layer_degree_bound")?;
+    if log_last_layer >= 64 {
+        return Err(format!(
+            "proof_params[3] (log_last_layer_degree_bound) is {}, too large",
+            log_last_layer
+        ));
+    }
+    let n_fri_steps = word(4, "n_fri_steps")? as usize;
+    if params.len() != 5 + n_fri_steps {
+        return Err(format!(
+            "proof_params declares {} FRI steps but has {} words after the count",
+            n_fri_steps,
+            params.len().saturating_sub(5)
+        ));
+    }
+
+    Ok(ProofParamsView {
+        n_queries: word(0, "n_queries")?,
+        log_n_cosets: word(1, "log_n_cosets")?,
+        proof_of_work_bits: word(2, "proof_of_work_bits")?,
+        last_layer_degree_bound: 1 << log_last_layer,
+        fri_step_sizes: (0..n_fri_steps)
+            .map(|step| word(5 + step, "a FRI step size"))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Exact verify transaction counts, taken from the same split the verify script performs
+pub fn estimate_transaction_counts(
+    proof: &SplitAnnotatedProof,
+) -> Result<TxCounts, Box<dyn std::error::Error>> {
+    let split_proofs = split_fri_merkle_statements(proof.clone())?;
+    let (_, continuous_pages) = split_proofs.main_proof.memory_page_registration_args();
+
+    Ok(TxCounts {
+        merkle_statements: split_proofs.merkle_statements.len(),
+        fri_statements: split_proofs.fri_merkle_statements.len(),
+        continuous_pages: continuous_pages.len(),
+    })
+}
+
+/// The parts `split_fri_merkle_statements` makes of an annotated proof, as JSON in the layout
+/// stark-evm-adapter's `gen-split-proofs` writes
+pub fn split_proof_parts(proof: &SplitAnnotatedProof) -> Result<serde_json::Value, String> {
+    let split_proofs = split_fri_merkle_statements(proof.clone()).map_err(|e| e.to_string())?;
+    serde_json::to_value(&split_proofs).map_err(|e| e.to_string())
+}
+
+/// Split proof parts put back side by side with the annotated proof they came from, for
+/// `prepare-input merge`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedProof {
+    /// 32-byte words of the annotated proof's proof_hex
+    pub proof_words: usize,
+    /// Words of the main proof's `proof`
+    pub main_proof_words: usize,
+    /// Trace Merkle statement names, sorted
+    pub trace_statements: Vec<String>,
+    /// Step size of each FRI statement, in order
+    pub fri_step_sizes: Vec<u64>,
+    /// Every way the parts don't reassemble into the annotated proof; empty when they do
+    pub mismatches: Vec<String>,
+}
+
+/// Reassemble split proof `parts` (as `split_proof_parts` returns them) against the annotated
+/// proof at `annotated_proof_path`: the main proof must carry the whole proof, there must be a
+/// Merkle statement per trace commitment and a FRI statement per FRI step after the first,
+/// each rooted at a commitment the annotations record
+pub fn merge_split_proof(
+    annotated_proof_path: &str,
+    parts: &serde_json::Value,
+) -> Result<MergedProof, String> {
+    merge_proof_parts(&parse_annotated_proof(annotated_proof_path), parts)
+}
+
+fn merge_proof_parts(
+    annotated_proof: &AnnotatedProof,
+    parts: &serde_json::Value,
+) -> Result<MergedProof, String> {
+    let part = |key: &str| {
+        parts
+            .get(key)
+            .ok_or_else(|| format!("Split proof has no {}", key))
+    };
+    let mut mismatches = Vec::new();
+
+    let mut proof_bytes = decode_hex(&annotated_proof.proof_hex);
+    proof_bytes.resize(proof_bytes.len().div_ceil(32) * 32, 0);
+    let proof_words: Vec<U256> = proof_bytes.chunks(32).map(U256::from_big_endian).collect();
+    let main_proof = part("main_proof")?;
+    let main_proof_words: Vec<U256> = field(main_proof, "proof", "main_proof")?;
+    if let Some(diff) = array_diff("proof", &proof_words, &main_proof_words) {
+        mismatches.push(format!(
+            "main_proof.proof has {} words to proof_hex's {}, first differing at {}",
+            diff.len_b, diff.len_a, diff.first_difference
+        ));
+    }
+
+    let hash = Regex::new(r"Commitment: Hash\((0x[0-9a-fA-F]+)\)").unwrap();
+    let commitments = |pattern: &str| -> Vec<U256> {
+        annotated_proof
+            .annotations
+            .iter()
+            .filter(|line| line.contains(pattern))
+            .filter_map(|line| hash.captures(line))
+            .filter_map(|cap| U256::from_str_radix(&cap[1], 16).ok())
+            .collect()
+    };
+    let unrooted = |name: &str, root: U256, commitments: &[U256]| {
+        (!commitments.contains(&root)).then(|| {
+            format!(
+                "{} is rooted at 0x{:x}, not an annotated commitment",
+                name, root
+            )
+        })
+    };
+
+    let trace_commitments = commitments("Commit on Trace");
+    let merkle_statements = part("merkle_statements")?
+        .as_object()
+        .ok_or("Split proof's merkle_statements is not a map")?;
+    let mut trace_statements: Vec<String> = merkle_statements.keys().cloned().collect();
+    trace_statements.sort();
+    if trace_statements.len() != trace_commitments.len() {
+        mismatches.push(format!(
+            "{} trace Merkle statements for {} trace commitments",
+            trace_statements.len(),
+            trace_commitments.len()
+        ));
+    }
+    for name in &trace_statements {
+        let root = field(&merkle_statements[name], "expected_root", name)?;
+        mismatches.extend(unrooted(name, root, &trace_commitments));
+    }
+
+    let fri_commitments = commitments("/FRI/Commitment/");
+    let fri_statements = part("fri_merkle_statements")?
+        .as_array()
+        .ok_or("Split proof's fri_merkle_statements is not a list")?;
+    let fri_step_list = &annotated_proof.proof_parameters.stark.fri.fri_step_list;
+    let expected_steps = fri_step_list.get(1..).unwrap_or_default();
+    let mut fri_step_sizes = Vec::new();
+    for (i, statement) in fri_statements.iter().enumerate() {
+        let name = format!("FRI statement {}", i);
+        // A plain number, or a U256 in the adapter's hex form
+        let step_size = match statement
+            .get("fri_step_size")
+            .and_then(|size| size.as_u64())
+        {
+            Some(step_size) => U256::from(step_size),
+            None => field::<U256>(statement, "fri_step_size", &name)?,
+        };
+        // Checked before narrowing; a wider value is a corrupt split, not a large step
+        if step_size > U256::from(MAX_FRI_STEP_SIZE) {
+            return Err(format!(
+                "{}.fri_step_size: {} is not a FRI step size (at most {})",
+                name, step_size, MAX_FRI_STEP_SIZE
+            ));
+        }
+        let step_size = step_size.as_u64();
+        // An extra statement is reported with the count below
+        if let Some(&expected) = expected_steps.get(i) {
+            if u64::from(expected) != step_size {
+                mismatches.push(format!(
+                    "{} steps {} layers where fri_step_list has {}",
+                    name, step_size, expected
+                ));
+            }
+        }
+        fri_step_sizes.push(step_size);
+        let root = field(statement, "expected_root", &name)?;
+        mismatches.extend(unrooted(&name, root, &fri_commitments));
+    }
+    if fri_statements.len() != expected_steps.len() {
+        mismatches.push(format!(
+            "{} FRI statements for {} FRI steps after the first",
+            fri_statements.len(),
+            expected_steps.len()
+        ));
+    }
+
+    Ok(MergedProof {
+        proof_words: proof_words.len(),
+        main_proof_words: main_proof_words.len(),
+        trace_statements,
+        fri_step_sizes,
+        mismatches,
+    })
+}
+
+/// Largest FRI step size a split proof may record; a step folds 2^step_size evaluations
+const MAX_FRI_STEP_SIZE: u64 = 32;
+
+/// `part[key]` of the split proof `name`
+fn field<T: serde::de::DeserializeOwned>(
+    part: &serde_json::Value,
+    key: &str,
+    name: &str,
+) -> Result<T, String> {
+    let value = part
+        .get(key)
+        .ok_or_else(|| format!("{} has no {}", name, key))?;
+    serde_json::from_value(value.clone()).map_err(|e| format!("{}.{}: {}", name, key, e))
+}
+
+/// Signature of GpsStatementVerifier's entry point for the main proof
+pub const VERIFY_PROOF_AND_REGISTER: &str =
+    "verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256)";
+
+/// Arguments of `verifyProofAndRegister(proofParams, proof, taskMetadata, cairoAuxInput,
+/// cairoVerifierId)`, in order
+pub fn verify_proof_and_register_args(
+    proof_params: &[U256],
+    proof: &[U256],
+    task_metadata: &[U256],
+    cairo_aux_input: &[U256],
+    cairo_verifier_id: U256,
+) -> Vec<Token> {
+    let uint_array =
+        |values: &[U256]| Token::Array(values.iter().map(|&v| Token::Uint(v)).collect());
+    vec![
+        uint_array(proof_params),
+        uint_array(proof),
+        uint_array(task_metadata),
+        uint_array(cairo_aux_input),
+        Token::Uint(cairo_verifier_id),
+    ]
+}
+
+/// Calldata for `verifyProofAndRegister`: the 4-byte selector followed by the standard ABI
+/// encoding of the four dynamic arrays and the verifier id.
+pub fn encode_verify_proof_and_register(
+    proof_params: &[U256],
+    proof: &[U256],
+    task_metadata: &[U256],
+    cairo_aux_input: &[U256],
+    cairo_verifier_id: U256,
+) -> Bytes {
+    let encoded = ethers::abi::encode(&verify_proof_and_register_args(
+        proof_params,
+        proof,
+        task_metadata,
+        cairo_aux_input,
+        cairo_verifier_id,
+    ));
+
+    let selector = &keccak256(VERIFY_PROOF_AND_REGISTER.as_bytes())[..4];
+    Bytes::from([selector, &encoded[..]].concat())
+}
+
+/// Calldata for a verifier entry point with a non-stock `signature`, such as a proxy that
+/// takes extra arguments after the standard ones.
+///
+/// `extra_args` are parsed as the types the signature declares after `args`; the signature
+/// must declare exactly as many parameters as are supplied, with matching types.
+pub fn encode_verify_call(
+    signature: &str,
+    mut args: Vec<Token>,
+    extra_args: &[String],
+) -> Result<Bytes, String> {
+    let signature = signature.trim();
+    let function = AbiParser::default()
+        .parse_function(&format!(
+            "function {}",
+            signature.strip_prefix("function ").unwrap_or(signature)
+        ))
+        .map_err(|e| format!("Invalid function signature {:?}: {}", signature, e))?;
+
+    let expected = args.len() + extra_args.len();
+    if function.inputs.len() != expected {
+        return Err(format!(
+            "{} takes {} arguments but {} are supplied ({} standard + {} extra)",
+            function.signature(),
+            function.inputs.len(),
+            expected,
+            args.len(),
+            extra_args.len()
+        ));
+    }
+    for (param, value) in function.inputs[args.len()..].iter().zip(extra_args) {
+        let token = LenientTokenizer::tokenize(&param.kind, value)
+            .map_err(|e| format!("Invalid {} argument {:?}: {}", param.kind, value, e))?;
+        args.push(token);
+    }
+
+    function
+        .encode_input(&args)
+        .map(Bytes::from)
+        .map_err(|e| format!("Arguments don't match {}: {}", function.signature(), e))
+}
+
+/// How one array field differs between two inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayDiff {
+    pub field: &'static str,
+    pub len_a: usize,
+    pub len_b: usize,
+    /// First index where the arrays differ, counting the end of the shorter one
+    pub first_difference: usize,
+}
+
+/// Structured differences between two verifier inputs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputDiff {
+    /// Array fields that differ in length or content, in field order
+    pub arrays: Vec<ArrayDiff>,
+    pub z_differs: bool,
+    pub alpha_differs: bool,
+    pub memory_page_facts_differ: bool,
+}
+
+impl InputDiff {
+    pub fn is_empty(&self) -> bool {
+        self.arrays.is_empty()
+            && !self.z_differs
+            && !self.alpha_differs
+            && !self.memory_page_facts_differ
+    }
+}
+
+/// Compare two verifier inputs field by field; the integrity hash is ignored
+pub fn diff_verifier_inputs(a: &VerifierInput, b: &VerifierInput) -> InputDiff {
+    let arrays = [
+        array_diff("proof_params", &a.proof_params, &b.proof_params),
+        array_diff("proof", &a.proof, &b.proof),
+        array_diff("public_input", &a.public_input, &b.public_input),
+        array_diff(
+            "memory_page_fact_hashes",
+            &a.memory_page_fact_hashes,
+            &b.memory_page_fact_hashes,
+        ),
+        array_diff("task_metadata", &a.task_metadata, &b.task_metadata),
+    ];
+
+    InputDiff {
+        arrays: arrays.into_iter().flatten().collect(),
+        z_differs: a.z != b.z,
+        alpha_differs: a.alpha != b.alpha,
+        memory_page_facts_differ: serde_json::to_value(&a.memory_page_facts).ok()
+            != serde_json::to_value(&b.memory_page_facts).ok(),
+    }
+}
+
+fn array_diff<T: PartialEq>(field: &'static str, a: &[T], b: &[T]) -> Option<ArrayDiff> {
+    let first_difference = a
+        .iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .or_else(|| (a.len() != b.len()).then_some(a.len().min(b.len())))?;
+    Some(ArrayDiff {
+        field,
+        len_a: a.len(),
+        len_b: b.len(),
+        first_difference,
+    })
+}
+
+/// Check that the page counts the main proof carries agree, returning every mismatch: the
+/// count public_input declares (followed by 4 words of info and product per page), the
+/// continuous pages' fact hashes, and the pages task_metadata's fact topologies cover, which
+/// must be every continuous page. Any mismatch reverts the main proof on-chain, e.g. with
+/// "Invalid publicMemoryPages length".
+pub fn check_page_counts(verifier_input: &VerifierInput) -> Vec<String> {
+    let pages = verifier_input.memory_page_facts.page_count();
+    let continuous_pages = verifier_input.memory_page_facts.continuous_page_count();
+    let mut mismatches = Vec::new();
+
+    let public_input = &verifier_input.public_input;
+    match public_input.len().checked_sub(4 * pages) {
+        Some(index) if public_input[index] == U256::from(pages) => {}
+        Some(index) => mismatches.push(format!(
+            "public_input declares {} memory pages (word {}), but the memory page facts have {}",
+            public_input[index], index, pages
+        )),
+        None => mismatches.push(format!(
+            "public_input has {} words, too few for the page info of {} memory pages",
+            public_input.len(),
+            pages
+        )),
+    }
+
+    let fact_hashes = verifier_input.memory_page_fact_hashes.len();
+    if fact_hashes != 0 && fact_hashes != continuous_pages {
+        mismatches.push(format!(
+            "memory_page_fact_hashes has {} entries for {} continuous pages",
+            fact_hashes, continuous_pages
+        ));
+    }
+
+    match task_metadata_pages(&verifier_input.task_metadata) {
+        Ok(covered) if covered == continuous_pages => {}
+        Ok(covered) => mismatches.push(format!(
+            "task_metadata's fact topologies cover {} pages, but there are {} continuous pages",
+            covered, continuous_pages
+        )),
+        Err(e) => mismatches.push(e),
+    }
+    mismatches
+}
+
+/// Word `index` of `metadata` as a count or size; these all fit in 32 bits, so a wider word
+/// (e.g. from a hand-edited --task-metadata file) is an error rather than a truncation
+fn metadata_count(metadata: &[U256], index: usize) -> Result<usize, String> {
+    let value = metadata
+        .get(index)
+        .ok_or_else(|| format!("task_metadata too short: no entry at {}", index))?;
+    if value.bits() > 32 {
+        return Err(format!("task_metadata word {} out of range", index));
+    }
+    Ok(value.as_usize())
+}
+
+/// Total pages over every task's (n_pages, n_nodes) tree pairs in `metadata`, laid out as
+/// [n_tasks, then per task: output_size, program_hash, n_tree_pairs, pairs...]
+fn task_metadata_pages(metadata: &[U256]) -> Result<usize, String> {
+    let word = |index: usize| metadata_count(metadata, index);
+
+    let mut offset = 1;
+    let mut pages = 0;
+    for _ in 0..word(0)? {
+        let n_tree_pairs = word(offset + 2)?;
+        for pair in 0..n_tree_pairs {
+            pages += word(offset + 3 + 2 * pair)?;
+        }
+        offset += 3 + 2 * n_tree_pairs;
+    }
+    if offset != metadata.len() {
+        return Err(format!(
+            "task_metadata has {} words, but its tasks account for {}",
+            metadata.len(),
+            offset
+        ));
+    }
+    Ok(pages)
+}
+
+/// Facts GpsStatementVerifier registers for each task once the main proof is verified,
+/// computed as registerGpsFacts does: keccak256(abi.encode(programHash, programOutputFact)),
+/// where programOutputFact is the root of the task's page tree from its fact topology.
+///
+/// Pages are taken from the continuous pages in order; the page address checks the contract
+/// performs are left to the contract.
+pub fn gps_task_facts(verifier_input: &VerifierInput) -> Result<Vec<[u8; 32]>, String> {
+    let metadata = &verifier_input.task_metadata;
+    let word = |index: usize| metadata_count(metadata, index);
+
+    let mut pages = verifier_input.memory_page_facts.continuous_pages.iter();
+    let mut offset = 1;
+    let mut facts = Vec::new();
+    for task in 0..word(0)? {
+        let program_hash = *metadata
+            .get(offset + 1)
+            .ok_or_else(|| format!("task_metadata too short for task {}", task))?;
+        let n_tree_pairs = word(offset + 2)?;
+
+        // (hash, end) nodes, as in GpsOutputParser's node stack
+        let mut stack: Vec<(U256, U256)> = Vec::new();
+        let mut task_offset = 0usize;
+        for pair in 0..n_tree_pairs {
+            let n_pages = word(offset + 3 + 2 * pair)?;
+            let n_nodes = word(offset + 3 + 2 * pair + 1)?;
+
+            for _ in 0..n_pages {
+                let page = pages.next().ok_or_else(|| {
+                    format!("Task {} needs more continuous pages than exist", task)
+                })?;
+                let values: Vec<u8> = page.values.iter().flat_map(bigint_to_bytes32).collect();
+                task_offset += page.values.len();
+                stack.push((
+                    U256::from_big_endian(&keccak256(&values)),
+                    U256::from(task_offset),
+                ));
+            }
+
+            if n_nodes != 0 {
+                if n_nodes > stack.len() {
+                    return Err(format!("Invalid n_nodes {} in task {}", n_nodes, task));
+                }
+                let children = stack.split_off(stack.len() - n_nodes);
+                let mut data = Vec::with_capacity(children.len() * 64);
+                for (hash, end) in &children {
+                    data.extend_from_slice(&u256_to_bytes32(hash));
+                    data.extend_from_slice(&u256_to_bytes32(end));
+                }
+                let (hash, _) =
+                    U256::from_big_endian(&keccak256(&data)).overflowing_add(U256::one());
+                stack.push((hash, children[children.len() - 1].1));
+            }
+        }
+        if stack.len() != 1 {
+            return Err(format!(
+                "Fact topology of task {} leaves {} nodes on the stack, expected 1",
+                task,
+                stack.len()
+            ));
+        }
+
+        let mut data = u256_to_bytes32(&program_hash).to_vec();
+        data.extend_from_slice(&u256_to_bytes32(&stack[0].0));
+        facts.push(keccak256(&data));
+
+        offset += 3 + 2 * n_tree_pairs;
+    }
+    Ok(facts)
+}
+
+fn u256_to_bytes32(val: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    val.to_big_endian(&mut bytes);
+    bytes
+}
+
+/// Left-pad a non-negative BigInt to a 32-byte big-endian word
+fn bigint_to_bytes32(val: &BigInt) -> [u8; 32] {
+    let mut bytes_32 = [0u8; 32];
+    let bytes = val.to_bytes_be().1;
+    bytes_32[32 - bytes.len()..].copy_from_slice(&bytes);
+    bytes_32
+}
+
+/// Fact hash for a continuous page, computed exactly as
+/// MemoryPageFactRegistry.registerContinuousMemoryPage does:
+/// keccak256(CONTINUOUS_PAGE, prime, nValues, z, alpha, prod, memoryHash, startAddr)
+fn continuous_page_fact_hash(
+    page: &MemoryPageContinuous,
+    z: &BigInt,
+    alpha: &BigInt,
+    prime: &BigInt,
+) -> [u8; 32] {
+    let mut prod = BigInt::one();
+    let mut values_bytes = Vec::with_capacity(page.values.len() * 32);
+    for (i, value) in page.values.iter().enumerate() {
+        let addr = &page.start_addr + BigInt::from(i);
+        let lin_comb = (addr + alpha * value) % prime;
+        prod = (prod * ((z - lin_comb + prime) % prime)) % prime;
+        values_bytes.extend_from_slice(&bigint_to_bytes32(value));
+    }
+    let memory_hash = BigInt::from_bytes_be(num_bigint::Sign::Plus, &keccak256(&values_bytes));
+
+    let fields = [
+        BigInt::one(), // CONTINUOUS_PAGE
+        prime.clone(),
+        BigInt::from(page.values.len()),
+        z.clone(),
+        alpha.clone(),
+        prod,
+        memory_hash,
+        page.start_addr.clone(),
+    ];
+    let mut packed = Vec::with_capacity(fields.len() * 32);
+    for field in &fields {
+        packed.extend_from_slice(&bigint_to_bytes32(field));
+    }
+    keccak256(&packed)
+}
+
 fn calculate_product(
     prod: U256,
     z: U256,
@@ -313,7 +1365,8 @@ fn proof_params(annotated_proof: &AnnotatedProof) -> Vec<BigInt> {
 }
 
 fn prepare_memory_page_facts(annotated_proof: &AnnotatedProof) -> MemoryPageFacts {
-    let mut pages: HashMap<u64, Vec<(BigInt, BigInt)>> = HashMap::new();
+    // BTreeMap keeps pages in ascending page order
+    let mut pages: BTreeMap<u64, Vec<(BigInt, BigInt)>> = BTreeMap::new();
 
     // Group memory cells by page
     for cell in &annotated_proof.public_input.public_memory {
@@ -326,7 +1379,7 @@ fn prepare_memory_page_facts(annotated_proof: &AnnotatedProof) -> MemoryPageFact
                 .as_bytes(),
             16,
         )
-        .expect(&format!("Failed to parse value: {}", cell.value));
+        .unwrap_or_else(|e| panic!("Failed to parse value {}: {}", cell.value, e));
 
         pages
             .entry(page as u64)
@@ -346,8 +1399,7 @@ fn prepare_memory_page_facts(annotated_proof: &AnnotatedProof) -> MemoryPageFact
 
     // Prepare continuous pages (page > 0)
     let mut continuous_pages = Vec::new();
-    let mut page_numbers: Vec<u64> = pages.keys().cloned().collect();
-    page_numbers.sort();
+    let page_numbers: Vec<u64> = pages.keys().cloned().collect();
 
     for page_num in page_numbers {
         let cells = pages[&page_num].clone();
@@ -433,7 +1485,8 @@ fn prepare_public_input_without_products(
     result.extend(serialize_segments(public_input));
 
     // Memory pages info WITHOUT products
-    let mut pages: HashMap<u64, Vec<BigInt>> = HashMap::new();
+    // BTreeMap keeps pages in ascending page order
+    let mut pages: BTreeMap<u64, Vec<BigInt>> = BTreeMap::new();
 
     for cell in &public_input.public_memory {
         let page = cell.page;
@@ -445,7 +1498,7 @@ fn prepare_public_input_without_products(
                 .as_bytes(),
             16,
         )
-        .expect(&format!("Failed to parse value: {}", cell.value));
+        .unwrap_or_else(|e| panic!("Failed to parse value {}: {}", cell.value, e));
 
         let page_data = pages.entry(page as u64).or_insert_with(Vec::new);
         page_data.push(address.clone());
@@ -491,8 +1544,7 @@ fn prepare_public_input_without_products(
     result.push(BigInt::from(n_pages));
 
     // Sort pages by page number (page 0 first, then 1, 2, etc.)
-    let mut page_numbers: Vec<u64> = pages.keys().cloned().collect();
-    page_numbers.sort();
+    let page_numbers: Vec<u64> = pages.keys().cloned().collect();
 
     // Add page info (size, hash, address if > 0) WITHOUT products
     for (idx, &page_num) in page_numbers.iter().enumerate() {
@@ -568,7 +1620,7 @@ fn extract_program_output(public_input: &PublicInput) -> Vec<BigInt> {
                 .as_bytes(),
             16,
         )
-        .expect(&format!("Failed to parse value: {}", cell.value));
+        .unwrap_or_else(|e| panic!("Failed to parse value {}: {}", cell.value, e));
         memory.insert(cell.address as u64, value);
     }
 
@@ -584,6 +1636,213 @@ fn extract_program_output(public_input: &PublicInput) -> Vec<BigInt> {
     output
 }
 
+/// Layout of a bootloader's program output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BootloaderFormat {
+    /// [bootloaderProgramHash, hashedVerifiers, nTasks, tasks...]
+    Full,
+    /// [nTasks, tasks...]
+    Simple,
+}
+
+impl BootloaderFormat {
+    fn description(self) -> &'static str {
+        match self {
+            BootloaderFormat::Full => "FULL bootloader format (with bootloader_config prefix)",
+            BootloaderFormat::Simple => "SIMPLE bootloader format",
+        }
+    }
+}
+
+/// Format of a bootloader's program output, its number of tasks and the index the first task
+/// starts at
+fn bootloader_tasks(output: &[BigInt]) -> (BootloaderFormat, usize, usize) {
+    // Detect full bootloader vs simple bootloader format:
+    // Full bootloader: [bootloaderProgramHash, hashedVerifiers, nTasks, ...]
+    // Simple bootloader: [nTasks, outputSize, programHash, ...]
+    // Full bootloader has large values at indices 0 and 1 (hashes), simple has small nTasks at 0
+    let is_full_bootloader = output.len() >= 3 && {
+        let val0 = &output[0];
+        // If output[0] > 2^32, it's likely a hash (full bootloader)
+        val0 > &BigInt::from(0x100000000u64)
+    };
+
+    let (format, n_tasks_index) = if is_full_bootloader {
+        // Full bootloader: nTasks at index 2, tasks start at index 3
+        (BootloaderFormat::Full, 2)
+    } else {
+        // Simple bootloader: nTasks at index 0, tasks start at index 1
+        (BootloaderFormat::Simple, 0)
+    };
+    let n = output
+        .get(n_tasks_index)
+        .map(|v| v.to_string().parse::<usize>().unwrap_or(0))
+        .unwrap_or(0);
+    (format, n, n_tasks_index + 1)
+}
+
+/// Task metadata for the GPS verifier, after checking the fact topologies describe exactly the
+/// tasks in the program output
+fn checked_tasks_metadata(
+    public_input: &PublicInput,
+    fact_topologies: &[FactTopology],
+) -> Result<Vec<BigInt>, String> {
+    if !fact_topologies.is_empty() {
+        let output = extract_program_output(public_input);
+        let (sizes, _) = task_output_sizes(&output)?;
+        if sizes.len() != fact_topologies.len() {
+            return Err(format!(
+                "The proof's program output has {} tasks but {} fact topologies were given",
+                sizes.len(),
+                fact_topologies.len()
+            ));
+        }
+    }
+    Ok(generate_tasks_metadata(public_input, fact_topologies))
+}
+
+/// Output size of each task in a bootloader's program output, checked against the output's
+/// length, and the index just past the last task
+fn task_output_sizes(output: &[BigInt]) -> Result<(Vec<usize>, usize), String> {
+    let (_, n_tasks, mut ptr) = bootloader_tasks(output);
+    let mut sizes = Vec::with_capacity(n_tasks);
+    for task in 0..n_tasks {
+        // Each task's output starts with its size (including this header) and program hash
+        let size = output
+            .get(ptr)
+            .filter(|_| ptr + 1 < output.len())
+            .and_then(|size| size.to_string().parse::<usize>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "Program output ends before the header of task {} (index {})",
+                    task, ptr
+                )
+            })?;
+        if size < 2 || ptr + size > output.len() {
+            return Err(format!(
+                "Task {} claims {} output values at index {}, but the program output has {}",
+                task,
+                size,
+                ptr,
+                output.len()
+            ));
+        }
+        sizes.push(size);
+        ptr += size;
+    }
+    Ok((sizes, ptr))
+}
+
+/// Where each of the `n_tasks` tasks of a bootloader's program output lies in public memory
+fn task_infos(public_input: &PublicInput, n_tasks: usize) -> Result<Vec<TaskInfo>, String> {
+    if n_tasks == 0 {
+        return Ok(Vec::new());
+    }
+    let output = extract_program_output(public_input);
+    let (sizes, _) = task_output_sizes(&output)?;
+    if sizes.len() != n_tasks {
+        return Err(format!(
+            "task_metadata lists {} tasks but the program output has {}",
+            n_tasks,
+            sizes.len()
+        ));
+    }
+    let output_begin = public_input
+        .memory_segments
+        .get("output")
+        .ok_or("Missing output segment")?
+        .begin_addr;
+
+    let (_, _, mut ptr) = bootloader_tasks(&output);
+    let mut tasks = Vec::with_capacity(n_tasks);
+    for size in sizes {
+        let start = output_begin + ptr as u64;
+        let end = start + size as u64;
+        let mut pages: Vec<u32> = public_input
+            .public_memory
+            .iter()
+            .filter(|cell| (start..end).contains(&(cell.address as u64)))
+            .map(|cell| cell.page)
+            .collect();
+        pages.sort_unstable();
+        pages.dedup();
+        tasks.push(TaskInfo {
+            program_hash: format!("0x{:x}", output[ptr + 1]),
+            output_start: start,
+            output_size: size,
+            pages,
+        });
+        ptr += size;
+    }
+    Ok(tasks)
+}
+
+/// Summarize an annotated proof's structure without preparing anything from it
+pub fn inspect_annotated_proof(annotated_proof_path: &str) -> ProofSummary {
+    summarize_proof(&parse_annotated_proof(annotated_proof_path))
+}
+
+fn summarize_proof(annotated_proof: &AnnotatedProof) -> ProofSummary {
+    let commitments = |pattern: &str| {
+        annotated_proof
+            .annotations
+            .iter()
+            .filter(|line| line.contains(pattern) && line.contains("Commitment: Hash"))
+            .count()
+    };
+    let mut memory_pages = BTreeMap::new();
+    for cell in &annotated_proof.public_input.public_memory {
+        *memory_pages.entry(cell.page).or_insert(0) += 1;
+    }
+    let fri = &annotated_proof.proof_parameters.stark.fri;
+    let proof_hex = &annotated_proof.proof_hex;
+
+    ProofSummary {
+        annotations: annotated_proof.annotations.len(),
+        proof_bytes: proof_hex.strip_prefix("0x").unwrap_or(proof_hex).len() / 2,
+        layout: annotated_proof.public_input.layout.clone(),
+        n_steps: annotated_proof.public_input.n_steps,
+        trace_commitments: commitments("Commit on Trace"),
+        fri_commitments: commitments("/FRI/Commitment/"),
+        fri_step_list: fri.fri_step_list.clone(),
+        n_queries: fri.n_queries,
+        last_layer_degree_bound: fri.last_layer_degree_bound,
+        memory_pages,
+        segments: annotated_proof
+            .public_input
+            .memory_segments
+            .iter()
+            .map(|(name, segment)| (name.clone(), (segment.begin_addr, segment.stop_ptr)))
+            .collect(),
+    }
+}
+
+/// Fact topologies for the tasks in the proof's bootloader output, assuming each task wrote
+/// its whole output as one page (the bootloader's default). A task that split its output
+/// into pages can't be told apart from the proof; take those from the bootloader's
+/// fact_topologies.json instead.
+pub fn derive_fact_topologies(annotated_proof_path: &str) -> Result<Vec<FactTopology>, String> {
+    let annotated_proof = parse_annotated_proof(annotated_proof_path);
+    let output = extract_program_output(&annotated_proof.public_input);
+    let (sizes, end) = task_output_sizes(&output)?;
+    if sizes.is_empty() || end != output.len() {
+        return Err(format!(
+            "{}: program output isn't a bootloader's ({} tasks covering {} of {} values)",
+            annotated_proof_path,
+            sizes.len(),
+            end,
+            output.len()
+        ));
+    }
+    Ok(sizes
+        .into_iter()
+        .map(|size| FactTopology {
+            tree_structure: vec![1, 0],
+            page_sizes: vec![size],
+        })
+        .collect())
+}
+
 /// Generate task metadata for GPS verifier from fact topologies
 fn generate_tasks_metadata(
     public_input: &PublicInput,
@@ -616,34 +1875,8 @@ fn generate_tasks_metadata(
     // For each task: outputSize, programHash, nTreePairs, tree_structure...
     // NOTE: bootloader config is in the proof OUTPUT, NOT in taskMetadata!
 
-    // Detect full bootloader vs simple bootloader format:
-    // Full bootloader: [bootloaderProgramHash, hashedVerifiers, nTasks, ...]
-    // Simple bootloader: [nTasks, outputSize, programHash, ...]
-    // Full bootloader has large values at indices 0 and 1 (hashes), simple has small nTasks at 0
-    let is_full_bootloader = output.len() >= 3 && {
-        let val0 = &output[0];
-        // If output[0] > 2^32, it's likely a hash (full bootloader)
-        val0 > &BigInt::from(0x100000000u64)
-    };
-
-    let (n_tasks, tasks_start_idx) = if is_full_bootloader {
-        println!("Detected FULL bootloader format (with bootloader_config prefix)");
-        // Full bootloader: nTasks at index 2, tasks start at index 3
-        let n = output
-            .get(2)
-            .map(|v| v.to_string().parse::<usize>().unwrap_or(0))
-            .unwrap_or(0);
-        (n, 3usize)
-    } else {
-        println!("Detected SIMPLE bootloader format");
-        // Simple bootloader: nTasks at index 0, tasks start at index 1
-        let n = output
-            .get(0)
-            .map(|v| v.to_string().parse::<usize>().unwrap_or(0))
-            .unwrap_or(0);
-        (n, 1usize)
-    };
-
+    let (format, n_tasks, tasks_start_idx) = bootloader_tasks(&output);
+    eprintln!("Detected {}", format.description());
     println!("n_tasks: {}", n_tasks);
 
     if n_tasks != fact_topologies.len() {
@@ -659,10 +1892,12 @@ fn generate_tasks_metadata(
     // RunProgramTask bootloader does write it
     // Check if first element is a small number (n_tasks) or large hash`
 
-    // Build task_metadata - starts with nTasks (no bootloader config here!)
-    let mut task_metadata = vec![BigInt::from(n_tasks)];
+    // Build task_metadata - starts with nTasks (no bootloader config here!), set to the
+    // number of tasks actually listed once they're known
+    let mut task_metadata = vec![BigInt::zero()];
     // Tasks start after bootloader header
     let mut ptr = tasks_start_idx;
+    let mut listed = 0;
 
     for (i, fact_topology) in fact_topologies.iter().enumerate() {
         if ptr >= output.len() {
@@ -690,7 +1925,9 @@ fn generate_tasks_metadata(
         }
 
         ptr += task_output_size;
+        listed += 1;
     }
+    task_metadata[0] = BigInt::from(listed);
 
     println!(
         "Generated task_metadata with {} elements",
@@ -715,33 +1952,140 @@ fn load_fact_topologies(base_path: &str) -> Vec<FactTopology> {
     ];
 
     for path in &possible_paths {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(fact_topologies_file) = serde_json::from_str::<FactTopologiesFile>(&content) {
-                println!("Loaded fact_topologies from: {}", path);
-                return fact_topologies_file.fact_topologies;
-            }
+        if let Ok(fact_topologies_file) = read_json_file::<FactTopologiesFile>(path) {
+            println!("Loaded fact_topologies from: {}", path);
+            return fact_topologies_file.fact_topologies;
         }
     }
 
     Vec::new()
 }
 
+/// Prepare the verifier input, with task metadata from a fact_topologies.json found next to
+/// the proof or in the working directory
 pub fn prepare_verifier_input(annotated_proof_path: &str) -> VerifierInput {
     let annotated_proof = parse_annotated_proof(annotated_proof_path);
+    check_hex_values(&annotated_proof)
+        .unwrap_or_else(|e| panic!("{}: {}", annotated_proof_path, e));
+    let fact_topologies = load_fact_topologies(annotated_proof_path);
+    let task_metadata = generate_tasks_metadata(&annotated_proof.public_input, &fact_topologies);
+    build_verifier_input(&annotated_proof, &task_metadata)
+        .unwrap_or_else(|e| panic!("{}: {}", annotated_proof_path, e))
+}
+
+/// Prepare the verifier input with task metadata computed from the given fact topologies,
+/// failing when they don't match the tasks in the proof
+pub fn prepare_verifier_input_with_topologies(
+    annotated_proof_path: &str,
+    fact_topologies_path: &str,
+) -> Result<VerifierInput, String> {
+    let fact_topologies: FactTopologiesFile =
+        read_json_file(fact_topologies_path).map_err(|e| e.to_string())?;
+
+    let annotated_proof = parse_annotated_proof(annotated_proof_path);
+    check_hex_values(&annotated_proof).map_err(|e| format!("{}: {}", annotated_proof_path, e))?;
+    let task_metadata = checked_tasks_metadata(
+        &annotated_proof.public_input,
+        &fact_topologies.fact_topologies,
+    )
+    .map_err(|e| format!("{}: {}", fact_topologies_path, e))?;
+    build_verifier_input(&annotated_proof, &task_metadata)
+        .map_err(|e| format!("{}: {}", annotated_proof_path, e))
+}
+
+/// Prepare the verifier input from an already parsed proof, with task metadata computed from
+/// `fact_topologies`, failing when they don't match the tasks in the proof
+pub fn prepare_verifier_input_from_proof(
+    proof: &SplitAnnotatedProof,
+    fact_topologies: &[FactTopology],
+) -> Result<VerifierInput, String> {
+    let annotated_proof: AnnotatedProof = serde_json::to_value(proof)
+        .and_then(serde_json::from_value)
+        .map_err(|e| format!("Failed to read the annotated proof: {}", e))?;
+    check_hex_values(&annotated_proof)?;
+    let task_metadata = checked_tasks_metadata(&annotated_proof.public_input, fact_topologies)?;
+    build_verifier_input(&annotated_proof, &task_metadata)
+}
+
+/// Check the proof's hex strings before anything is derived from them, naming the first
+/// malformed one: `proof_hex` must be hex digits and every public memory value a hex uint256
+fn check_hex_values(annotated_proof: &AnnotatedProof) -> Result<(), String> {
+    let proof_hex = &annotated_proof.proof_hex;
+    let digits = proof_hex.strip_prefix("0x").unwrap_or(proof_hex);
+    if let Some((offset, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "proof_hex has non-hex character {:?} at digit {}",
+            c, offset
+        ));
+    }
+
+    for (index, cell) in annotated_proof
+        .public_input
+        .public_memory
+        .iter()
+        .enumerate()
+    {
+        let value = BigInt::parse_bytes(
+            cell.value
+                .strip_prefix("0x")
+                .unwrap_or(&cell.value)
+                .as_bytes(),
+            16,
+        )
+        .ok_or_else(|| {
+            format!(
+                "public_memory[{}] value {:?} is not a hex number",
+                index, cell.value
+            )
+        })?;
+        bigint_to_u256(&value)
+            .map_err(|e| format!("public_memory[{}] value {}: {}", index, cell.value, e))?;
+    }
+    Ok(())
+}
+
+/// `value` as a uint256 word, if it is one
+fn bigint_to_u256(value: &BigInt) -> Result<U256, String> {
+    let (sign, bytes) = value.to_bytes_be();
+    if sign == num_bigint::Sign::Minus {
+        return Err("negative".to_string());
+    }
+    if bytes.len() > 32 {
+        return Err(format!("{} bits, over uint256", value.bits()));
+    }
+    Ok(U256::from_big_endian(&bytes))
+}
+
+/// Every element of `values` as a uint256 word, naming `field` and the index of the first
+/// that isn't one
+fn u256_words(field: &str, values: &[BigInt]) -> Result<Vec<U256>, String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            bigint_to_u256(value)
+                .map_err(|e| format!("{}[{}] = 0x{:x}: {}", field, index, value, e))
+        })
+        .collect()
+}
 
+fn build_verifier_input(
+    annotated_proof: &AnnotatedProof,
+    task_metadata: &[BigInt],
+) -> Result<VerifierInput, String> {
     // Convert proof
     let proof = proof_hex_to_int_list(&annotated_proof.proof_hex);
 
     // Prepare proof parameters
-    let proof_params = proof_params(&annotated_proof);
+    let proof_params = proof_params(annotated_proof);
 
     // IMPORTANT: Prepare memory page facts FIRST (without z and alpha)
     // This prepares the data structure, but products will be computed later
-    let memory_page_facts = prepare_memory_page_facts(&annotated_proof);
+    let memory_page_facts = prepare_memory_page_facts(annotated_proof);
 
     // Prepare public input WITHOUT page products (for hash calculation)
     let public_input_without_products =
-        prepare_public_input_without_products(&annotated_proof, &memory_page_facts);
+        prepare_public_input_without_products(annotated_proof, &memory_page_facts);
 
     // Extract z and alpha from annotations
     // NOTE: We use annotations instead of computing from hash because the verifier
@@ -749,25 +2093,22 @@ pub fn prepare_verifier_input(annotated_proof_path: &str) -> VerifierInput {
     let (z, alpha) = extract_interaction_elements(&annotated_proof.annotations);
 
     // Convert z and alpha to U256
-    let z_u256 = U256::from_str(&format!("0x{:x}", z)).unwrap();
-    let alpha_u256 = U256::from_str(&format!("0x{:x}", alpha)).unwrap();
+    let z_u256 = bigint_to_u256(&z).map_err(|e| format!("z: {}", e))?;
+    let alpha_u256 = bigint_to_u256(&alpha).map_err(|e| format!("alpha: {}", e))?;
 
     // Convert public_input_without_products to Vec<U256>
-    let mut public_input: Vec<U256> = public_input_without_products
-        .iter()
-        .map(|bi| U256::from_str(&format!("0x{:x}", bi)).unwrap())
-        .collect();
+    let mut public_input = u256_words("public_input", &public_input_without_products)?;
 
     // Calculate page products using U256
     let k_modulus = U256::from_str_radix(K_MODULUS_STR.strip_prefix("0x").unwrap(), 16).unwrap();
 
-    let mut page_prods: HashMap<u64, U256> = HashMap::new();
+    let mut page_prods: BTreeMap<u64, U256> = BTreeMap::new();
 
     for cell in &annotated_proof.public_input.public_memory {
         let page = cell.page;
         let address = U256::from(cell.address);
         let value = U256::from_str_radix(cell.value.strip_prefix("0x").unwrap_or(&cell.value), 16)
-            .expect(&format!("Failed to parse value: {}", cell.value));
+            .unwrap_or_else(|e| panic!("Failed to parse value {}: {}", cell.value, e));
 
         let prod = page_prods.entry(page as u64).or_insert_with(U256::one);
         *prod = calculate_product(*prod, z_u256, alpha_u256, address, value, k_modulus);
@@ -775,8 +2116,7 @@ pub fn prepare_verifier_input(annotated_proof_path: &str) -> VerifierInput {
 
     // Add page products to public_input (in sorted order)
     // NOTE: Products must be added AFTER all page info (address/size/hash)
-    let mut page_numbers: Vec<u64> = page_prods.keys().cloned().collect();
-    page_numbers.sort();
+    let page_numbers: Vec<u64> = page_prods.keys().cloned().collect();
     eprintln!("DEBUG: Adding {} products", page_numbers.len());
     for &page_num in &page_numbers {
         eprintln!(
@@ -787,31 +2127,667 @@ pub fn prepare_verifier_input(annotated_proof_path: &str) -> VerifierInput {
     }
     eprintln!("DEBUG: Final public_input length: {}", public_input.len());
 
-    // Load fact topologies and generate task metadata
-    let fact_topologies = load_fact_topologies(annotated_proof_path);
-    let task_metadata = generate_tasks_metadata(&annotated_proof.public_input, &fact_topologies);
-
-    // Convert all BigInt vectors to U256 vectors
-    let proof_u256: Vec<U256> = proof
+    // Fact hashes the registry will store for each continuous page
+    let prime = BigInt::parse_bytes(K_MODULUS_STR.strip_prefix("0x").unwrap().as_bytes(), 16)
+        .expect("Failed to parse K_MODULUS");
+    let memory_page_fact_hashes: Vec<String> = memory_page_facts
+        .continuous_pages
         .iter()
-        .map(|bi| U256::from_str(&format!("0x{:x}", bi)).unwrap())
-        .collect();
-    let proof_params_u256: Vec<U256> = proof_params
-        .iter()
-        .map(|bi| U256::from_str(&format!("0x{:x}", bi)).unwrap())
-        .collect();
-    let task_metadata_u256: Vec<U256> = task_metadata
-        .iter()
-        .map(|bi| U256::from_str(&format!("0x{:x}", bi)).unwrap())
+        .map(|page| {
+            let fact_hash = continuous_page_fact_hash(page, &z, &alpha, &prime);
+            format!("0x{}", ethers::utils::hex::encode(fact_hash))
+        })
         .collect();
 
-    VerifierInput {
+    // Convert all BigInt vectors to U256 vectors
+    let proof_u256 = u256_words("proof", &proof)?;
+    let proof_params_u256 = u256_words("proof_params", &proof_params)?;
+    let task_metadata_u256 = u256_words("task_metadata", task_metadata)?;
+    let n_tasks = task_metadata
+        .first()
+        .and_then(|n| n.to_string().parse::<usize>().ok())
+        .unwrap_or(0);
+    // Only a warning here: inputs prepared without checked topologies may not match the output
+    let tasks = task_infos(&annotated_proof.public_input, n_tasks).unwrap_or_else(|e| {
+        eprintln!("WARNING: Not listing tasks: {}", e);
+        Vec::new()
+    });
+
+    Ok(VerifierInput {
         proof_params: proof_params_u256,
         proof: proof_u256,
         public_input,
         z: z_u256,
         alpha: alpha_u256,
         memory_page_facts,
+        memory_page_fact_hashes,
         task_metadata: task_metadata_u256,
+        integrity: None,
+        tasks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Minimal starknet-layout annotated proof with a regular page and one continuous page
+    const ANNOTATED_PROOF: &str = r#"{
+        "annotations": [
+            "V->P: /cpu air/STARK/Interaction: Interaction element #0: Field Element(0x1234)",
+            "V->P: /cpu air/STARK/Interaction: Interaction element #1: Field Element(0x5678)"
+        ],
+        "proof_hex": "0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021",
+        "public_input": {
+            "layout": "starknet",
+            "memory_segments": {
+                "program": { "begin_addr": 1, "stop_ptr": 5 },
+                "execution": { "begin_addr": 10, "stop_ptr": 20 },
+                "output": { "begin_addr": 20, "stop_ptr": 22 },
+                "pedersen": { "begin_addr": 30, "stop_ptr": 30 },
+                "range_check": { "begin_addr": 40, "stop_ptr": 40 }
+            },
+            "n_steps": 32768,
+            "public_memory": [
+                { "address": 1, "page": 0, "value": "0x40780017fff7fff" },
+                { "address": 2, "page": 0, "value": "0x4" },
+                { "address": 20, "page": 1, "value": "0x2a" },
+                { "address": 21, "page": 1, "value": "0x2b" }
+            ],
+            "rc_min": 32762,
+            "rc_max": 32769
+        },
+        "proof_parameters": {
+            "stark": {
+                "log_n_cosets": 2,
+                "fri": {
+                    "n_queries": 16,
+                    "proof_of_work_bits": 30,
+                    "last_layer_degree_bound": 64,
+                    "fri_step_list": [0, 4, 4, 4, 1]
+                }
+            }
+        }
+    }"#;
+
+    /// Scratch directory for one test, removed when dropped so a failing assert doesn't
+    /// leave it behind
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path =
+                std::env::temp_dir().join(format!("prepare_input_{}_{}", name, std::process::id()));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        /// Write `contents` to file `name` in the directory and return its path
+        fn write(&self, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// The verifier input prepared from the fixture proof, from a scratch directory `name`
+    fn fixture_input(name: &str) -> VerifierInput {
+        let dir = TempDir::new(name);
+        let path = dir.write("annotated_proof.json", ANNOTATED_PROOF);
+        prepare_verifier_input(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn prepare_verifier_input_is_deterministic() {
+        let dir = TempDir::new("deterministic");
+        let path = dir.write("annotated_proof.json", ANNOTATED_PROOF);
+        let path = path.to_str().unwrap();
+
+        let first = serde_json::to_string_pretty(&prepare_verifier_input(path)).unwrap();
+        let second = serde_json::to_string_pretty(&prepare_verifier_input(path)).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn committed_examples_load() {
+        let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+        let mut loaded = Vec::new();
+        for entry in fs::read_dir(&examples).unwrap() {
+            let path = entry.unwrap().path().join("input.json");
+            if !path.is_file() {
+                continue;
+            }
+            let input = read_verifier_input(&path).unwrap_or_else(|e| panic!("{}", e));
+            let name = path.parent().unwrap().file_name().unwrap().to_owned();
+            // The examples predate memory_page_fact_hashes, and factorial has no task_metadata
+            assert!(input.memory_page_fact_hashes.is_empty());
+            assert_eq!(input.task_metadata.is_empty(), name == "factorial");
+            loaded.push(name);
+        }
+        assert_eq!(loaded.len(), 4);
+    }
+
+    /// The fixture's public input with its output segment replaced by `values`
+    fn public_input_with_output(values: &[&str]) -> PublicInput {
+        let mut proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+        let output = proof
+            .public_input
+            .memory_segments
+            .get_mut("output")
+            .unwrap();
+        output.stop_ptr = output.begin_addr + values.len() as u64;
+        let begin = output.begin_addr as u32;
+        proof
+            .public_input
+            .public_memory
+            .retain(|cell| cell.page == 0);
+        for (i, value) in values.iter().enumerate() {
+            proof.public_input.public_memory.push(PublicMemory {
+                address: begin + i as u32,
+                page: 1,
+                value: value.to_string(),
+            });
+        }
+        proof.public_input
+    }
+
+    fn topology() -> FactTopology {
+        FactTopology {
+            tree_structure: vec![1, 0],
+            page_sizes: vec![2],
+        }
+    }
+
+    #[test]
+    fn task_metadata_from_matching_topologies() {
+        // One task: output size 2 (just its header) and program hash 0xabc
+        let public_input = public_input_with_output(&["0x1", "0x2", "0xabc"]);
+
+        let metadata = checked_tasks_metadata(&public_input, &[topology()]).unwrap();
+        assert_eq!(metadata, [1, 2, 0xabc, 1, 1, 0].map(BigInt::from).to_vec());
+    }
+
+    #[test]
+    fn derived_topologies_give_each_task_one_page() {
+        let dir = TempDir::new("topologies");
+        let mut proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+
+        // Two tasks: one with just its header, one with a single output value
+        proof.public_input =
+            public_input_with_output(&["0x2", "0x2", "0xabc", "0x3", "0xdef", "0x7"]);
+        let path = dir.write(
+            "annotated_proof.json",
+            serde_json::to_string(&proof).unwrap(),
+        );
+        let topologies = derive_fact_topologies(path.to_str().unwrap()).unwrap();
+        let page_sizes: Vec<&[usize]> = topologies.iter().map(|t| &t.page_sizes[..]).collect();
+        assert_eq!(page_sizes, [&[2][..], &[3][..]]);
+        assert!(topologies.iter().all(|t| t.tree_structure == [1, 0]));
+        assert!(checked_tasks_metadata(&proof.public_input, &topologies).is_ok());
+
+        // Values past the last task mean this isn't a bootloader's output
+        proof.public_input = public_input_with_output(&["0x1", "0x2", "0xabc", "0x5"]);
+        let path = dir.write(
+            "annotated_proof.json",
+            serde_json::to_string(&proof).unwrap(),
+        );
+        assert!(derive_fact_topologies(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn two_task_proof_lists_each_tasks_output() {
+        let dir = TempDir::new("tasks");
+        let mut proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+
+        // Two tasks: 0xabc with one output value, 0xdef with two; the second task's output
+        // is registered as its own page
+        proof.public_input =
+            public_input_with_output(&["0x2", "0x3", "0xabc", "0x7", "0x4", "0xdef", "0x8", "0x9"]);
+        let output_begin = proof.public_input.memory_segments["output"].begin_addr;
+        for cell in &mut proof.public_input.public_memory {
+            if cell.page == 1 && u64::from(cell.address) >= output_begin + 4 {
+                cell.page = 2;
+            }
+        }
+        let path = dir.write(
+            "annotated_proof.json",
+            serde_json::to_string(&proof).unwrap(),
+        );
+        let topologies = FactTopologiesFile {
+            fact_topologies: derive_fact_topologies(path.to_str().unwrap()).unwrap(),
+        };
+        let topologies_path = dir.write(
+            "fact_topologies.json",
+            serde_json::to_string(&topologies).unwrap(),
+        );
+
+        let input = prepare_verifier_input_with_topologies(
+            path.to_str().unwrap(),
+            topologies_path.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            input.task_metadata,
+            [2, 3, 0xabc, 1, 1, 0, 4, 0xdef, 1, 1, 0]
+                .map(U256::from)
+                .to_vec()
+        );
+        assert_eq!(
+            input.tasks,
+            [
+                TaskInfo {
+                    program_hash: "0xabc".to_string(),
+                    output_start: output_begin + 1,
+                    output_size: 3,
+                    pages: vec![1],
+                },
+                TaskInfo {
+                    program_hash: "0xdef".to_string(),
+                    output_start: output_begin + 4,
+                    output_size: 4,
+                    pages: vec![2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_describes_the_fixture() {
+        let mut proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+        proof.annotations.extend([
+            "P->V[0:32]: /cpu air/STARK/Original/Commit on Trace: Commitment: Hash(0x01)"
+                .to_string(),
+            "P->V[32:64]: /cpu air/STARK/FRI/Commitment/Layer 1: Commitment: Hash(0x02)"
+                .to_string(),
+        ]);
+        let summary = summarize_proof(&proof);
+
+        assert_eq!(summary.annotations, 4);
+        assert_eq!(summary.proof_bytes, 33);
+        assert_eq!(summary.trace_commitments, 1);
+        assert_eq!(summary.fri_commitments, 1);
+        assert_eq!(summary.fri_step_list, [0, 4, 4, 4, 1]);
+        assert_eq!(summary.memory_pages, BTreeMap::from([(0, 2), (1, 2)]));
+        assert_eq!(summary.segments["output"], (20, 22));
+        assert_eq!(summary.segments.len(), 5);
+    }
+
+    #[test]
+    fn proof_params_decode_to_the_fixture_parameters() {
+        let proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+        let params: Vec<U256> = proof_params(&proof)
+            .iter()
+            .map(|value| U256::from_dec_str(&value.to_string()).unwrap())
+            .collect();
+
+        let view = decode_proof_params(&params).unwrap();
+        assert_eq!(view.n_queries, 16);
+        assert_eq!(view.log_n_cosets, 2);
+        assert_eq!(view.proof_of_work_bits, 30);
+        assert_eq!(view.last_layer_degree_bound, 64);
+        assert_eq!(view.fri_step_sizes, [0, 4, 4, 4, 1]);
+
+        assert!(decode_proof_params(&params[..4]).is_err());
+        assert!(decode_proof_params(&params[..params.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn inconsistent_page_counts_are_reported() {
+        let mut input = fixture_input("pages");
+
+        // One task whose single tree pair covers the fixture's one continuous page
+        input.task_metadata = [1, 2, 0xabc, 1, 1, 0].map(U256::from).to_vec();
+        assert_eq!(check_page_counts(&input), Vec::<String>::new());
+
+        let mut two_pages = input.clone();
+        two_pages.task_metadata[4] = U256::from(2);
+        assert_eq!(check_page_counts(&two_pages).len(), 1);
+
+        let mut truncated = input.clone();
+        truncated.task_metadata.pop();
+        assert_eq!(check_page_counts(&truncated).len(), 1);
+
+        let n_pages = input.public_input.len() - 8;
+        input.public_input[n_pages] = U256::from(3);
+        assert_eq!(check_page_counts(&input).len(), 1);
+    }
+
+    #[test]
+    fn oversized_task_metadata_word_is_an_error() {
+        let mut input = fixture_input("oversized_metadata");
+        input.task_metadata = [1, 2, 0xabc, 1, 1, 0].map(U256::from).to_vec();
+        input.task_metadata[3] = U256::one() << 64;
+        assert_eq!(
+            gps_task_facts(&input),
+            Err("task_metadata word 3 out of range".to_string())
+        );
+        assert_eq!(
+            check_page_counts(&input),
+            vec!["task_metadata word 3 out of range".to_string()]
+        );
+    }
+
+    #[test]
+    fn split_parts_merge_back_into_the_fixture() {
+        let mut proof: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+        proof.annotations.extend([
+            "P->V[0:32]: /cpu air/STARK/Original/Commit on Trace: Commitment: Hash(0x01)"
+                .to_string(),
+            "P->V[32:64]: /cpu air/STARK/FRI/Commitment/Layer 1: Commitment: Hash(0x02)"
+                .to_string(),
+        ]);
+        let fri_statement =
+            |step: u64| serde_json::json!({ "expected_root": "0x2", "fri_step_size": step });
+        let mut parts = serde_json::json!({
+            "main_proof": {
+                "proof": [
+                    "0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+                    format!("0x21{}", "00".repeat(31)),
+                ]
+            },
+            "merkle_statements": { "Trace 0": { "expected_root": "0x1" } },
+            "fri_merkle_statements": [fri_statement(4), fri_statement(4), fri_statement(4), fri_statement(1)],
+        });
+
+        let merged = merge_proof_parts(&proof, &parts).unwrap();
+        assert_eq!(merged.proof_words, 2);
+        assert_eq!(merged.main_proof_words, 2);
+        assert_eq!(merged.trace_statements, ["Trace 0"]);
+        assert_eq!(merged.fri_step_sizes, [4, 4, 4, 1]);
+        assert!(merged.mismatches.is_empty(), "{:?}", merged.mismatches);
+
+        parts["main_proof"]["proof"][1] = serde_json::json!("0x21");
+        parts["merkle_statements"]["Trace 0"]["expected_root"] = serde_json::json!("0x3");
+        parts["fri_merkle_statements"].as_array_mut().unwrap().pop();
+        let merged = merge_proof_parts(&proof, &parts).unwrap();
+        assert_eq!(
+            merged.mismatches,
+            [
+                "main_proof.proof has 2 words to proof_hex's 2, first differing at 1",
+                "Trace 0 is rooted at 0x3, not an annotated commitment",
+                "3 FRI statements for 4 FRI steps after the first",
+            ]
+        );
+
+        parts["fri_merkle_statements"][0]["fri_step_size"] =
+            serde_json::json!(format!("0x1{}", "0".repeat(16)));
+        assert_eq!(
+            merge_proof_parts(&proof, &parts).unwrap_err(),
+            "FRI statement 0.fri_step_size: 18446744073709551616 is not a FRI step size (at most 32)"
+        );
+
+        parts.as_object_mut().unwrap().remove("main_proof");
+        assert_eq!(
+            merge_proof_parts(&proof, &parts).unwrap_err(),
+            "Split proof has no main_proof"
+        );
+    }
+
+    #[test]
+    fn mismatched_topologies_are_rejected() {
+        let public_input = public_input_with_output(&["0x2", "0x2", "0xabc"]);
+
+        let err = checked_tasks_metadata(&public_input, &[topology()]).unwrap_err();
+        assert!(err.contains("has 2 tasks but 1 fact topologies"), "{}", err);
+
+        // The count matches, but the output ends after the first task
+        let err = checked_tasks_metadata(&public_input, &[topology(), topology()]).unwrap_err();
+        assert!(err.contains("header of task 1"), "{}", err);
+    }
+
+    #[test]
+    fn prepared_input_matches_schema() {
+        let input = serde_json::to_value(fixture_input("schema")).unwrap();
+        assert_eq!(validate_verifier_input(&input), Vec::<String>::new());
+
+        let mut broken = input;
+        broken["z"] = serde_json::json!(42);
+        broken.as_object_mut().unwrap().remove("proof");
+        assert_eq!(validate_verifier_input(&broken).len(), 2);
+    }
+
+    #[test]
+    fn verify_proof_and_register_encoding_matches_golden_vector() {
+        let words = |values: &[u64]| values.iter().map(|&v| U256::from(v)).collect::<Vec<_>>();
+        let calldata = encode_verify_proof_and_register(
+            &words(&[1]),
+            &words(&[2, 3]),
+            &[],
+            &words(&[4]),
+            U256::from(5),
+        );
+
+        // Selector, then a head of four array offsets and the verifier id, then each array as
+        // its length followed by its elements
+        let expected = [
+            "9b3b76cc",
+            "00000000000000000000000000000000000000000000000000000000000000a0",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000140",
+            "0000000000000000000000000000000000000000000000000000000000000160",
+            "0000000000000000000000000000000000000000000000000000000000000005",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+        ]
+        .concat();
+        assert_eq!(ethers::utils::hex::encode(&calldata), expected);
+    }
+
+    #[test]
+    fn cbor_round_trip_matches_json() {
+        let dir = TempDir::new("cbor");
+        let input = fixture_input("cbor_proof");
+
+        let cbor_path = dir.write("input.cbor", verifier_input_to_cbor(&input).unwrap());
+        let decoded = read_verifier_input(&cbor_path).unwrap();
+
+        assert_eq!(decoded.proof_params, input.proof_params);
+        assert_eq!(decoded.proof, input.proof);
+        assert_eq!(decoded.public_input, input.public_input);
+        assert_eq!(decoded.task_metadata, input.task_metadata);
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&input).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_hex_values_are_located() {
+        let valid: AnnotatedProof = serde_json::from_str(ANNOTATED_PROOF).unwrap();
+        assert_eq!(check_hex_values(&valid), Ok(()));
+
+        let mut bad_value = valid.clone();
+        bad_value.public_input.public_memory[2].value = "0x2g".to_string();
+        let error = check_hex_values(&bad_value).unwrap_err();
+        assert!(error.starts_with("public_memory[2]"), "{}", error);
+
+        let mut oversized = valid.clone();
+        oversized.public_input.public_memory[1].value = format!("0x1{}", "0".repeat(64));
+        let error = check_hex_values(&oversized).unwrap_err();
+        assert!(error.starts_with("public_memory[1]"), "{}", error);
+
+        let mut bad_proof = valid;
+        bad_proof.proof_hex = "0x0102x3".to_string();
+        let error = check_hex_values(&bad_proof).unwrap_err();
+        assert!(error.contains("at digit 4"), "{}", error);
+
+        let error = u256_words("proof", &[BigInt::one(), BigInt::one() << 256]).unwrap_err();
+        assert!(error.starts_with("proof[1]"), "{}", error);
+    }
+
+    #[test]
+    fn input_file_name_follows_proof_name() {
+        for (proof, expected) in [
+            ("fibonacci.annotated.json", "fibonacci.input.json"),
+            (
+                "proofs/fibonacci.annotated_proof.json",
+                "fibonacci.input.json",
+            ),
+            ("fibonacci_annotated_proof.json", "fibonacci_input.json"),
+            ("examples/factorial/annotated_proof.json", "input.json"),
+            ("factorial.json", "factorial.input.json"),
+            ("fibonacci.annotated.json.gz", "fibonacci.input.json"),
+        ] {
+            assert_eq!(input_file_name(proof, "json"), expected, "{}", proof);
+        }
+        assert_eq!(
+            input_file_name("fibonacci.annotated.json", "cbor"),
+            "fibonacci.input.cbor"
+        );
+    }
+
+    #[test]
+    fn compressed_inputs_read_like_plain_ones() {
+        let dir = TempDir::new("gz");
+        let input = fixture_input("gz_proof");
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gz, ANNOTATED_PROOF.as_bytes()).unwrap();
+        let gz_path = dir.write("annotated_proof.json.gz", gz.finish().unwrap());
+        let from_gz = prepare_verifier_input(gz_path.to_str().unwrap());
+
+        let cbor = verifier_input_to_cbor(&input).unwrap();
+        let cbor_path = dir.write("input.cbor.zst", zstd::encode_all(&cbor[..], 0).unwrap());
+        let from_zst = read_verifier_input(&cbor_path).unwrap();
+
+        for decoded in [from_gz, from_zst] {
+            assert_eq!(
+                serde_json::to_string(&decoded).unwrap(),
+                serde_json::to_string(&input).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn input_field_aliases_are_resolved() {
+        let mut fields = serde_json::json!({
+            "proofParams": [],
+            "PUBLIC_INPUT": [],
+            "proof": [],
+            "task_metadata": [],
+        });
+        let fields = fields.as_object_mut().unwrap();
+        let mut renamed = resolve_input_aliases(fields).unwrap();
+        renamed.sort();
+
+        assert_eq!(
+            renamed,
+            vec![
+                ("PUBLIC_INPUT".to_string(), "public_input"),
+                ("proofParams".to_string(), "proof_params"),
+            ]
+        );
+        let mut keys: Vec<&String> = fields.keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["proof", "proof_params", "public_input", "task_metadata"]
+        );
+
+        let mut ambiguous = serde_json::json!({ "publicInput": [], "public_input": [] });
+        let error = resolve_input_aliases(ambiguous.as_object_mut().unwrap()).unwrap_err();
+        assert!(
+            error.starts_with("public_input is given more than once"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn diff_reports_length_deltas_and_first_differences() {
+        let a = fixture_input("diff");
+        assert!(diff_verifier_inputs(&a, &a).is_empty());
+
+        let mut b = a.clone();
+        b.proof.push(U256::from(7));
+        b.public_input[1] += U256::one();
+        b.alpha += U256::one();
+        let diff = diff_verifier_inputs(&a, &b);
+
+        assert_eq!(
+            diff.arrays,
+            vec![
+                ArrayDiff {
+                    field: "proof",
+                    len_a: a.proof.len(),
+                    len_b: a.proof.len() + 1,
+                    first_difference: a.proof.len(),
+                },
+                ArrayDiff {
+                    field: "public_input",
+                    len_a: a.public_input.len(),
+                    len_b: a.public_input.len(),
+                    first_difference: 1,
+                },
+            ]
+        );
+        assert!(!diff.z_differs);
+        assert!(diff.alpha_differs);
+        assert!(!diff.memory_page_facts_differ);
+    }
+
+    #[test]
+    fn integrity_hash_detects_edits() {
+        let input = fixture_input("integrity").with_integrity();
+
+        assert!(input.check_integrity().is_ok());
+
+        let json = serde_json::to_string(&input).unwrap();
+        let reloaded: VerifierInput = serde_json::from_str(&json).unwrap();
+        assert!(reloaded.check_integrity().is_ok());
+
+        let mut edited = reloaded;
+        edited.proof.pop();
+        assert!(edited.check_integrity().is_err());
+    }
+
+    #[test]
+    fn custom_verify_signature_encoding() {
+        let args = || {
+            verify_proof_and_register_args(
+                &[U256::from(1)],
+                &[U256::from(2), U256::from(3)],
+                &[],
+                &[U256::from(4)],
+                U256::from(5),
+            )
+        };
+        let stock = encode_verify_proof_and_register(
+            &[U256::from(1)],
+            &[U256::from(2), U256::from(3)],
+            &[],
+            &[U256::from(4)],
+            U256::from(5),
+        );
+        assert_eq!(
+            encode_verify_call(VERIFY_PROOF_AND_REGISTER, args(), &[]).unwrap(),
+            stock
+        );
+
+        let proxy =
+            "verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256,address)";
+        let extra = vec!["0x00000000000000000000000000000000000000aa".to_string()];
+        let calldata = encode_verify_call(proxy, args(), &extra).unwrap();
+        // The extra static argument lands in the head, so every array offset moves by a word
+        assert_eq!(calldata.len(), stock.len() + 32);
+        assert_eq!(calldata[4 + 5 * 32 + 31], 0xaa);
+
+        assert!(encode_verify_call(proxy, args(), &[]).is_err());
+        assert!(encode_verify_call(VERIFY_PROOF_AND_REGISTER, args(), &extra).is_err());
     }
 }
@@ -1,29 +1,583 @@
-use prepare_input::prepare_verifier_input;
-use std::env;
+use clap::{Parser, Subcommand, ValueEnum};
+use common::read_json_file;
+use prepare_input::{
+    decode_proof_params, derive_fact_topologies, diff_verifier_inputs, estimate_transaction_counts,
+    forge_test_input, input_file_name, inspect_annotated_proof, merge_split_proof,
+    prepare_verifier_input, prepare_verifier_input_with_topologies, read_verifier_input,
+    split_proof_parts, validate_verifier_input, verifier_input_schema, verifier_input_to_cbor,
+    FactTopologiesFile, ProofBundle, VerifierInput,
+};
+use stark_evm_adapter::annotated_proof::AnnotatedProof;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(name = "prepare-input", version = env!("BUILD_VERSION"))]
+#[command(about = "Prepare verifier input.json from an annotated proof")]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// Path to annotated_proof.json file
+    annotated_proof: Option<String>,
+
+    /// Output file (defaults to input.json, or a name derived from the proof's with --out-dir)
+    output: Option<String>,
+
+    /// Write into this directory (created if missing) under a name derived from the proof's,
+    /// e.g. fibonacci.annotated.json -> fibonacci.input.json
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    out_dir: Option<PathBuf>,
+
+    /// Output layout
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+
+    /// Embed a keccak256 `integrity` hash of the proof and public input, checked by the verify
+    /// script before anything is sent
+    #[arg(long)]
+    integrity: bool,
+
+    /// Compute task_metadata from this fact_topologies.json, failing if it doesn't match the
+    /// proof's tasks (otherwise one is looked for next to the proof)
+    #[arg(long, value_name = "PATH")]
+    fact_topologies: Option<String>,
+
+    /// Also write fact_topologies.json for the verify script here, derived from the proof's
+    /// bootloader output with each task's output as one page, and compute task_metadata from
+    /// it. Tasks that split their output into pages need the bootloader's own file instead.
+    #[arg(long, value_name = "PATH", conflicts_with = "fact_topologies")]
+    emit_topologies: Option<String>,
+
+    /// Prepare and validate the input and print its summary without writing anything
+    #[arg(long, conflicts_with = "emit_topologies")]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Full VerifierInput, as read by the verify script
+    Json,
+    /// The layout test_VerifyProof reads, ready for examples/<program>/input.json
+    Forge,
+    /// Full VerifierInput as compact CBOR; the verify script reads it from a .cbor file
+    Cbor,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Print proof statistics and the transaction counts the verify script will send, without
+    /// writing output
+    Stats {
+        /// Path to annotated_proof.json file
+        annotated_proof: String,
+    },
+    /// Print a tree of what an annotated proof contains: annotations, commitments, FRI
+    /// layers, memory pages and public input segments, without writing output
+    Inspect {
+        /// Path to annotated_proof.json file
+        annotated_proof: String,
+    },
+    /// Print the exact Merkle/FRI/page transaction counts the verify script will send
+    Counts {
+        /// Path to annotated_proof.json file
+        annotated_proof: String,
+    },
+    /// Put the parts the verify script splits a proof into back together and check they
+    /// match the annotated proof: the main proof's words, and the trace and FRI statements'
+    /// counts, step sizes and roots
+    Merge {
+        /// Path to annotated_proof.json file
+        annotated_proof: String,
+
+        /// Split proof JSON, as stark-evm-adapter's gen-split-proofs writes it (split from the
+        /// annotated proof if omitted)
+        #[arg(long, value_name = "PATH")]
+        split_proofs: Option<String>,
+
+        /// Also write the split parts here, for a closer look
+        #[arg(long, value_name = "PATH", conflicts_with = "split_proofs")]
+        emit_split: Option<String>,
+    },
+    /// Print the FRI and query parameters a prepared input's proof_params encode
+    Params {
+        /// Prepared input.json (or .cbor)
+        input: String,
+
+        /// Print them as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the JSON Schema for input.json, or validate an existing file against it
+    Schema {
+        /// Validate this input.json against the schema instead of printing it
+        #[arg(long, value_name = "INPUT_JSON")]
+        validate: Option<String>,
+    },
+    /// Report how two input.json (or .cbor) files differ: array length deltas, the first
+    /// differing index of each array, and whether z or alpha changed
+    Diff {
+        /// First input file
+        a: String,
+
+        /// Second input file
+        b: String,
+    },
+    /// Combine the proof, input, fact topologies and deployment addresses into one bundle file
+    Bundle {
+        /// Path to annotated_proof.json file
+        #[arg(long)]
+        annotated_proof: String,
+
+        /// Path to fact_topologies.json file
+        #[arg(long)]
+        fact_topologies: String,
+
+        /// Path to an existing input.json (prepared from the annotated proof if omitted)
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Path to deployment-addresses.json to include in the bundle
+        #[arg(long)]
+        deployment_addresses: Option<String>,
+
+        /// Output file
+        #[arg(short, long, default_value = "bundle.json")]
+        output: String,
+    },
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let cli = Cli::parse();
 
-    if args.len() < 2 {
-        eprintln!("Usage: prepare-input <annotated_proof.json> [output.json]");
-        std::process::exit(1);
+    match cli.command {
+        Some(Commands::Stats { annotated_proof }) => print_stats(&annotated_proof),
+        Some(Commands::Counts { annotated_proof }) => print_counts(&annotated_proof),
+        Some(Commands::Inspect { annotated_proof }) => print_inspection(&annotated_proof),
+        Some(Commands::Merge {
+            annotated_proof,
+            split_proofs,
+            emit_split,
+        }) => merge_split(
+            &annotated_proof,
+            split_proofs.as_deref(),
+            emit_split.as_deref(),
+        ),
+        Some(Commands::Schema { validate: None }) => {
+            let schema = serde_json::to_string_pretty(&verifier_input_schema())
+                .expect("Failed to serialize schema");
+            println!("{}", schema);
+        }
+        Some(Commands::Schema {
+            validate: Some(input_path),
+        }) => validate_input(&input_path),
+        Some(Commands::Params { input, json }) => print_params(&input, json),
+        Some(Commands::Diff { a, b }) => diff_inputs(&a, &b),
+        Some(Commands::Bundle {
+            annotated_proof,
+            fact_topologies,
+            input,
+            deployment_addresses,
+            output,
+        }) => write_bundle(
+            &annotated_proof,
+            &fact_topologies,
+            input.as_deref(),
+            deployment_addresses.as_deref(),
+            &output,
+        ),
+        None => {
+            let Some(annotated_proof_path) = cli.annotated_proof else {
+                eprintln!(
+                    "Usage: prepare-input <annotated_proof.json> [output.json | --out-dir <dir>]"
+                );
+                std::process::exit(1);
+            };
+            let output = match (&cli.out_dir, cli.output) {
+                (Some(dir), _) => {
+                    let extension = match cli.format {
+                        Format::Json | Format::Forge => "json",
+                        Format::Cbor => "cbor",
+                    };
+                    dir.join(input_file_name(&annotated_proof_path, extension))
+                }
+                (None, Some(output)) => PathBuf::from(output),
+                (None, None) => PathBuf::from("input.json"),
+            };
+            let fact_topologies = match &cli.emit_topologies {
+                Some(path) => {
+                    write_topologies(&annotated_proof_path, path);
+                    Some(path.as_str())
+                }
+                None => cli.fact_topologies.as_deref(),
+            };
+            write_input(
+                &annotated_proof_path,
+                fact_topologies,
+                &output,
+                cli.format,
+                cli.integrity,
+                cli.dry_run,
+            );
+        }
     }
+}
 
-    let annotated_proof_path = &args[1];
-    let output_path = args.get(2).map(|s| s.as_str()).unwrap_or("input.json");
-
+fn write_input(
+    annotated_proof_path: &str,
+    fact_topologies_path: Option<&str>,
+    output_path: &Path,
+    format: Format,
+    integrity: bool,
+    dry_run: bool,
+) {
     println!("Preparing input from {}...", annotated_proof_path);
-    let verifier_input = prepare_verifier_input(annotated_proof_path);
-
-    let json_output =
-        serde_json::to_string_pretty(&verifier_input).expect("Failed to serialize output");
+    let mut verifier_input = match fact_topologies_path {
+        Some(path) => prepare_verifier_input_with_topologies(annotated_proof_path, path)
+            .unwrap_or_else(|e| panic!("{}", e)),
+        None => prepare_verifier_input(annotated_proof_path),
+    };
+    if integrity {
+        verifier_input = verifier_input.with_integrity();
+    }
 
-    fs::write(output_path, json_output)
-        .expect(&format!("Failed to write output to: {}", output_path));
+    let output: Vec<u8> = match format {
+        Format::Json => serde_json::to_string_pretty(&verifier_input)
+            .expect("Failed to serialize output")
+            .into_bytes(),
+        Format::Forge => serde_json::to_string_pretty(
+            &forge_test_input(&verifier_input).unwrap_or_else(|e| panic!("{}", e)),
+        )
+        .expect("Failed to serialize output")
+        .into_bytes(),
+        Format::Cbor => verifier_input_to_cbor(&verifier_input)
+            .unwrap_or_else(|e| panic!("Failed to serialize output: {}", e)),
+    };
 
-    println!("Input prepared and saved to {}", output_path);
+    if dry_run {
+        println!(
+            "Dry run - input prepared, not writing {}",
+            output_path.display()
+        );
+    } else {
+        if let Some(dir) = output_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            fs::create_dir_all(dir)
+                .unwrap_or_else(|e| panic!("Failed to create {}: {}", dir.display(), e));
+        }
+        fs::write(output_path, output).unwrap_or_else(|e| {
+            panic!("Failed to write output to {}: {}", output_path.display(), e)
+        });
+        println!("Input prepared and saved to {}", output_path.display());
+    }
     println!("Proof params length: {}", verifier_input.proof_params.len());
     println!("Proof length: {}", verifier_input.proof.len());
     println!("Public input length: {}", verifier_input.public_input.len());
+    println!(
+        "Task metadata length: {}",
+        verifier_input.task_metadata.len()
+    );
+    if let Some(hash) = &verifier_input.integrity {
+        println!("Integrity hash: {}", hash);
+    }
+}
+
+fn write_topologies(annotated_proof_path: &str, output_path: &str) {
+    let fact_topologies =
+        derive_fact_topologies(annotated_proof_path).unwrap_or_else(|e| panic!("{}", e));
+    let output = serde_json::to_string_pretty(&FactTopologiesFile {
+        fact_topologies: fact_topologies.clone(),
+    })
+    .expect("Failed to serialize fact topologies");
+    if let Some(dir) = Path::new(output_path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|e| panic!("Failed to create {}: {}", dir.display(), e));
+    }
+    fs::write(output_path, output)
+        .unwrap_or_else(|e| panic!("Failed to write fact topologies to {}: {}", output_path, e));
+
+    println!(
+        "Fact topologies for {} tasks saved to {}",
+        fact_topologies.len(),
+        output_path
+    );
+}
+
+fn print_stats(annotated_proof_path: &str) {
+    println!("Loading {}...", annotated_proof_path);
+    let verifier_input = prepare_verifier_input(annotated_proof_path);
+    let counts = estimate_transaction_counts(&read_json(annotated_proof_path))
+        .unwrap_or_else(|e| panic!("Failed to split proof: {}", e));
+
+    println!();
+    println!("Proof statistics:");
+    println!(
+        "  Proof params length: {}",
+        verifier_input.proof_params.len()
+    );
+    println!("  Proof length: {}", verifier_input.proof.len());
+    println!(
+        "  Public input length: {}",
+        verifier_input.public_input.len()
+    );
+    println!(
+        "  Memory pages: {}",
+        verifier_input.memory_page_facts.page_count()
+    );
+    println!();
+    println!("On-chain transactions:");
+    println!("  Merkle statements: {}", counts.merkle_statements);
+    println!("  FRI statements: {}", counts.fri_statements);
+    println!(
+        "  Continuous page registrations: {}",
+        counts.continuous_pages
+    );
+    println!("  Main proof: 1");
+    println!("  Total: {}", counts.total());
+}
+
+fn print_inspection(annotated_proof_path: &str) {
+    let summary = inspect_annotated_proof(annotated_proof_path);
+
+    println!("{}", annotated_proof_path);
+    println!("├── Layout: {} ({} steps)", summary.layout, summary.n_steps);
+    println!("├── Annotations: {}", summary.annotations);
+    println!("├── Proof: {} bytes", summary.proof_bytes);
+    println!("├── Commitments");
+    println!("│   ├── Trace: {}", summary.trace_commitments);
+    println!("│   └── FRI layers: {}", summary.fri_commitments);
+    println!("├── FRI");
+    println!(
+        "│   ├── Layers: {} (steps {:?})",
+        summary.fri_step_list.len(),
+        summary.fri_step_list
+    );
+    println!("│   ├── Queries: {}", summary.n_queries);
+    println!(
+        "│   └── Last layer degree bound: {}",
+        summary.last_layer_degree_bound
+    );
+    println!("├── Memory pages: {}", summary.memory_pages.len());
+    for (i, (page, cells)) in summary.memory_pages.iter().enumerate() {
+        let branch = if i + 1 == summary.memory_pages.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        let kind = if *page == 0 { "regular" } else { "continuous" };
+        println!("│   {} Page {} ({}): {} cells", branch, page, kind, cells);
+    }
+    println!("└── Segments: {}", summary.segments.len());
+    for (i, (name, (begin, stop))) in summary.segments.iter().enumerate() {
+        let branch = if i + 1 == summary.segments.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        println!(
+            "    {} {}: {}..{} ({} cells)",
+            branch,
+            name,
+            begin,
+            stop,
+            stop.saturating_sub(*begin)
+        );
+    }
+}
+
+fn print_counts(annotated_proof_path: &str) {
+    let annotated_proof: AnnotatedProof = read_json(annotated_proof_path);
+
+    let counts = estimate_transaction_counts(&annotated_proof)
+        .unwrap_or_else(|e| panic!("Failed to split proof: {}", e));
+
+    println!("Transaction counts for {}:", annotated_proof_path);
+    println!("  Merkle statements: {}", counts.merkle_statements);
+    println!("  FRI statements: {}", counts.fri_statements);
+    println!(
+        "  Continuous page registrations: {}",
+        counts.continuous_pages
+    );
+    println!("  Main proof: 1");
+    println!("  Total: {}", counts.total());
+}
+
+fn merge_split(annotated_proof_path: &str, split_proofs: Option<&str>, emit_split: Option<&str>) {
+    let parts: serde_json::Value = match split_proofs {
+        Some(path) => read_json(path),
+        None => {
+            let annotated_proof: AnnotatedProof = read_json(annotated_proof_path);
+            split_proof_parts(&annotated_proof)
+                .unwrap_or_else(|e| panic!("Failed to split proof: {}", e))
+        }
+    };
+    if let Some(path) = emit_split {
+        let json = serde_json::to_string_pretty(&parts).expect("Failed to serialize split proof");
+        fs::write(path, json).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+        println!("Wrote split proof to {}", path);
+    }
+
+    let merged = merge_split_proof(annotated_proof_path, &parts).unwrap_or_else(|e| {
+        eprintln!("❌ {}: {}", split_proofs.unwrap_or(annotated_proof_path), e);
+        std::process::exit(1);
+    });
+
+    println!("{} reassembled from its split:", annotated_proof_path);
+    println!(
+        "  main proof: {} of {} words",
+        merged.main_proof_words, merged.proof_words
+    );
+    println!(
+        "  trace Merkle statements: {}",
+        merged.trace_statements.join(", ")
+    );
+    println!("  FRI statement step sizes: {:?}", merged.fri_step_sizes);
+    if merged.mismatches.is_empty() {
+        println!("✅ The split parts match the annotated proof");
+        return;
+    }
+
+    println!("❌ {} mismatch(es):", merged.mismatches.len());
+    for mismatch in &merged.mismatches {
+        println!("  - {}", mismatch);
+    }
+    std::process::exit(1);
+}
+
+fn validate_input(input_path: &str) {
+    let input: serde_json::Value = read_json(input_path);
+
+    let violations = validate_verifier_input(&input);
+    if violations.is_empty() {
+        println!("{} matches the input.json schema", input_path);
+        return;
+    }
+
+    eprintln!("{} does not match the input.json schema:", input_path);
+    for violation in &violations {
+        eprintln!("  {}", violation);
+    }
+    std::process::exit(1);
+}
+
+fn print_params(input_path: &str, json: bool) {
+    let input = read_verifier_input(Path::new(input_path)).unwrap_or_else(|e| panic!("{}", e));
+    let view = decode_proof_params(&input.proof_params)
+        .unwrap_or_else(|e| panic!("{}: {}", input_path, e));
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&view).expect("Failed to serialize parameters")
+        );
+        return;
+    }
+
+    println!("{} proof parameters:", input_path);
+    println!("  n_queries:               {}", view.n_queries);
+    println!("  log_n_cosets:            {}", view.log_n_cosets);
+    println!("  proof_of_work_bits:      {}", view.proof_of_work_bits);
+    println!(
+        "  last_layer_degree_bound: {}",
+        view.last_layer_degree_bound
+    );
+    println!("  fri_step_sizes:          {:?}", view.fri_step_sizes);
+    // Every step after the first is its own FRI statement transaction
+    println!(
+        "  FRI statements:          {}",
+        view.fri_step_sizes.len().saturating_sub(1)
+    );
+}
+
+fn diff_inputs(a_path: &str, b_path: &str) {
+    let read = |path: &str| {
+        read_verifier_input(std::path::Path::new(path)).unwrap_or_else(|e| panic!("{}", e))
+    };
+    let (a, b) = (read(a_path), read(b_path));
+
+    let diff = diff_verifier_inputs(&a, &b);
+    if diff.is_empty() {
+        println!("{} and {} are equivalent", a_path, b_path);
+        return;
+    }
+
+    println!("{} vs {}:", a_path, b_path);
+    for array in &diff.arrays {
+        let length = if array.len_a == array.len_b {
+            format!("same length ({})", array.len_a)
+        } else {
+            format!(
+                "{} vs {} elements ({:+})",
+                array.len_a,
+                array.len_b,
+                array.len_b as i64 - array.len_a as i64
+            )
+        };
+        println!(
+            "  {}: {}, first difference at index {}",
+            array.field, length, array.first_difference
+        );
+    }
+    for (field, differs, value_a, value_b) in [
+        ("z", diff.z_differs, a.z, b.z),
+        ("alpha", diff.alpha_differs, a.alpha, b.alpha),
+    ] {
+        if differs {
+            println!("  {}: 0x{:x} vs 0x{:x}", field, value_a, value_b);
+        } else {
+            println!("  {}: same", field);
+        }
+    }
+    if diff.memory_page_facts_differ {
+        println!("  memory_page_facts: differ");
+    }
+    std::process::exit(1);
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    read_json_file(path).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn write_bundle(
+    annotated_proof_path: &str,
+    fact_topologies_path: &str,
+    input_path: Option<&str>,
+    deployment_addresses_path: Option<&str>,
+    output_path: &str,
+) {
+    println!("Bundling {}...", annotated_proof_path);
+    let input: VerifierInput = match input_path {
+        Some(path) => read_json(path),
+        None => prepare_verifier_input_with_topologies(annotated_proof_path, fact_topologies_path)
+            .unwrap_or_else(|e| panic!("{}", e)),
+    };
+    let fact_topologies: FactTopologiesFile = read_json(fact_topologies_path);
+
+    let bundle = ProofBundle {
+        annotated_proof: read_json(annotated_proof_path),
+        input,
+        fact_topologies: fact_topologies.fact_topologies,
+        deployment_addresses: deployment_addresses_path.map(read_json),
+    };
+
+    let file = fs::File::create(output_path)
+        .unwrap_or_else(|e| panic!("Failed to write output to {}: {}", output_path, e));
+    serde_json::to_writer(std::io::BufWriter::new(file), &bundle)
+        .expect("Failed to serialize bundle");
+
+    println!("Bundle saved to {}", output_path);
+    println!("  Fact topologies: {}", bundle.fact_topologies.len());
+    println!(
+        "  Deployment addresses: {}",
+        if bundle.deployment_addresses.is_some() {
+            "included"
+        } else {
+            "not included"
+        }
+    );
 }
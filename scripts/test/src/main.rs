@@ -1,102 +1,361 @@
 use clap::{Parser, Subcommand};
+use common::output::set_json_output;
+use common::{forge_command, info, load_env_file, resolve_forge_bin, spawn_error, Config};
+use serde_json::{json, Value};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use summary::TestResult;
+
+mod rerun;
+mod summary;
 
 #[derive(Parser)]
-#[command(name = "test")]
+#[command(name = "test", version = env!("BUILD_VERSION"))]
 #[command(about = "Test STARK verifier with example programs")]
 struct Cli {
+    /// Path to a playground.toml config file (defaults to ./playground.toml if present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Load env vars from this dotenv file (e.g. .env.staging) before reading any, over
+    /// those already set in the shell
+    #[arg(long, global = true, value_name = "PATH")]
+    env_file: Option<PathBuf>,
+
+    /// Stream raw forge output instead of printing a parsed summary
+    #[arg(long, global = true)]
+    raw: bool,
+
+    /// Path to the forge binary (overrides FORGE_BIN, defaults to `forge` on PATH)
+    #[arg(long, global = true)]
+    forge_bin: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Test a program example (fibonacci, factorial)
+    /// Test one or more program examples from the examples/ directory
     Example {
-        /// Program name (fibonacci or factorial)
-        program: String,
+        /// Program names (subdirectories of examples/, e.g. fibonacci factorial)
+        #[arg(required = true)]
+        programs: Vec<String>,
         /// Test bootloader version
         #[arg(long)]
         bootloader: bool,
+        /// Only run tests matching this pattern
+        #[arg(long, default_value = "test_VerifyProof")]
+        match_test: String,
+        /// Only run tests in contracts matching this pattern
+        #[arg(long)]
+        match_contract: Option<String>,
+        /// Print a JSON array with each example's `{program, bootloader, input_path, result,
+        /// gas}` on stdout; all other output goes to stderr
+        #[arg(long)]
+        json: bool,
     },
     /// Run all Forge tests
     All {
         /// Show gas report
         #[arg(long)]
         gas: bool,
+        /// Also write the gas report to this file (implies --gas; a .json path uses forge's JSON gas report)
+        #[arg(long)]
+        gas_report_out: Option<PathBuf>,
+        /// Only re-run the tests that failed in the last run, as recorded in
+        /// cache/test-runner-failed.json (runs without --raw or a gas report record it)
+        #[arg(long, conflicts_with_all = ["gas", "gas_report_out"])]
+        only_failed: bool,
     },
 }
 
+/// List example programs under `examples/` that contain an `input.json`.
+///
+/// Returns regular examples and bootloader examples (with the `-bootloader` suffix stripped) separately.
+fn discover_examples() -> (Vec<String>, Vec<String>) {
+    let mut regular = Vec::new();
+    let mut bootloader = Vec::new();
+
+    if let Ok(entries) = fs::read_dir("examples") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.join("input.json").is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            match name.strip_suffix("-bootloader") {
+                Some(program) => bootloader.push(program.to_string()),
+                None => regular.push(name.to_string()),
+            }
+        }
+    }
+
+    regular.sort();
+    bootloader.sort();
+    (regular, bootloader)
+}
+
+fn format_examples(examples: &[String]) -> String {
+    if examples.is_empty() {
+        "(none)".to_string()
+    } else {
+        examples.join(", ")
+    }
+}
+
+/// Run forge and report whether every test passed, with each test's result when forge's
+/// output could be parsed.
+///
+/// In raw mode forge output is streamed as-is; otherwise forge is run with `--json`
+/// and a compact summary is printed.
+fn run_forge(
+    mut cmd: Command,
+    raw: bool,
+) -> Result<(bool, Option<Vec<TestResult>>), Box<dyn std::error::Error>> {
+    if raw {
+        return Ok((
+            cmd.status().map_err(|e| spawn_error(&cmd, e))?.success(),
+            None,
+        ));
+    }
+
+    cmd.arg("--json");
+    let output = cmd.output().map_err(|e| spawn_error(&cmd, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    match summary::parse_forge_json(&stdout) {
+        Ok(results) => {
+            let failed = summary::print_summary(&results);
+            Ok((output.status.success() && failed == 0, Some(results)))
+        }
+        Err(e) => {
+            // Fall back to showing what forge printed so the failure is not hidden
+            eprintln!("⚠️  Could not parse forge output: {}", e);
+            info!("{}", stdout.trim_end());
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            Ok((output.status.success(), None))
+        }
+    }
+}
+
+/// Run forge with `--gas-report`, echo its output and save the report to `path`
+fn capture_gas_report(mut cmd: Command, path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    let json = path.extension().and_then(|e| e.to_str()) == Some("json");
+    if json {
+        cmd.arg("--json");
+    }
+
+    let output = cmd.output().map_err(|e| spawn_error(&cmd, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    print!("{}", stdout);
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+
+    fs::write(path, stdout.as_bytes())
+        .map_err(|e| format!("Failed to write gas report to {}: {}", path.display(), e))?;
+    println!(
+        "  Gas report saved to {}{}",
+        path.display(),
+        if json { " (JSON)" } else { "" }
+    );
+
+    Ok(output.status.success())
+}
+
+/// Run forge against a single example's input.json and report whether it passed, with the
+/// gas its tests used
+fn run_example(
+    program: &str,
+    bootloader: bool,
+    match_test: &str,
+    match_contract: Option<&str>,
+    raw: bool,
+    forge_bin: Option<&str>,
+) -> Result<(bool, Option<u64>), Box<dyn std::error::Error>> {
+    let example_dir = example_dir(program, bootloader);
+    let input_json_path = Path::new(&example_dir).join("input.json");
+
+    if !input_json_path.exists() {
+        eprintln!("❌ Error: input.json not found in {}", example_dir);
+        let (regular, bootloader_examples) = discover_examples();
+        eprintln!("Available examples: {}", format_examples(&regular));
+        eprintln!(
+            "Available bootloader examples (--bootloader): {}",
+            format_examples(&bootloader_examples)
+        );
+        if bootloader {
+            eprintln!("  make bootloader PROGRAM={}", program);
+        } else {
+            eprintln!("  make simple-flow PROGRAM={} LAYOUT=starknet", program);
+        }
+        eprintln!(
+            "  cp work/{}-starknet/input.json ../ethereum_verifier/{}/",
+            program, example_dir
+        );
+        return Err(format!("input.json not found in {}", example_dir).into());
+    }
+
+    info!(
+        "🧪 Testing program: {} ({})",
+        program,
+        if bootloader { "bootloader" } else { "regular" }
+    );
+
+    // Point the Solidity test at the example instead of overwriting ./input.json
+    info!("  Using input {}", input_json_path.display());
+
+    // Run forge test
+    let mut cmd = forge_command(forge_bin);
+    cmd.arg("test")
+        .arg("--match-test")
+        .arg(match_test)
+        .env("VERIFIER_TEST_INPUT", &input_json_path);
+    if let Some(match_contract) = match_contract {
+        cmd.arg("--match-contract").arg(match_contract);
+    }
+
+    let (passed, results) = run_forge(cmd, raw)?;
+    let gas = results.map(|results| results.iter().filter_map(|result| result.gas).sum());
+    if passed {
+        info!("✅ Test complete: {}", program);
+    } else {
+        info!("❌ Test failed: {}", program);
+    }
+    Ok((passed, gas))
+}
+
+/// Directory holding an example's input.json: examples/<program>, or
+/// examples/<program>-bootloader
+fn example_dir(program: &str, bootloader: bool) -> String {
+    if bootloader {
+        format!("examples/{}-bootloader", program)
+    } else {
+        format!("examples/{}", program)
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    if let Some(env_file) = &cli.env_file {
+        load_env_file(env_file)?;
+    }
+    let config = Config::load(cli.config.as_deref())?;
+    cli.forge_bin = resolve_forge_bin(cli.forge_bin.take(), &config);
 
     match cli.command {
         Commands::Example {
-            program,
+            programs,
             bootloader,
+            match_test,
+            match_contract,
+            json,
         } => {
-            let example_dir = if bootloader {
-                format!("examples/{}-bootloader", program)
-            } else {
-                format!("examples/{}", program)
-            };
+            if json && cli.raw {
+                return Err("--json reports from forge's parsed output; drop --raw".into());
+            }
+            set_json_output(json);
 
-            let input_json_path = Path::new(&example_dir).join("input.json");
+            let mut outcomes = Vec::new();
+            let mut records: Vec<Value> = Vec::new();
+            for program in &programs {
+                let (result, passed, gas) = match run_example(
+                    program,
+                    bootloader,
+                    &match_test,
+                    match_contract.as_deref(),
+                    cli.raw,
+                    cli.forge_bin.as_deref(),
+                ) {
+                    Ok((true, gas)) => ("passed", true, gas),
+                    Ok((false, gas)) => ("failed", false, gas),
+                    Err(e) => {
+                        eprintln!("❌ {}: {}", program, e);
+                        ("error", false, None)
+                    }
+                };
+                outcomes.push((program.as_str(), passed));
+                records.push(json!({
+                    "program": program,
+                    "bootloader": bootloader,
+                    "input_path": Path::new(&example_dir(program, bootloader)).join("input.json"),
+                    "result": result,
+                    "gas": gas,
+                }));
+            }
 
-            if !input_json_path.exists() {
-                eprintln!("❌ Error: input.json not found in {}", example_dir);
-                eprintln!("Available examples: fibonacci, factorial");
-                if bootloader {
-                    eprintln!("  make bootloader PROGRAM={}", program);
-                } else {
-                    eprintln!("  make simple-flow PROGRAM={} LAYOUT=starknet", program);
-                }
-                eprintln!(
-                    "  cp work/{}-starknet/input.json ../ethereum_verifier/{}/",
-                    program, example_dir
+            let failed = outcomes.iter().filter(|(_, passed)| !passed).count();
+            let width = programs.iter().map(|p| p.len()).max().unwrap_or(0);
+            info!();
+            info!("📋 Example results:");
+            for (program, passed) in &outcomes {
+                info!(
+                    "  {:<width$}  {}",
+                    program,
+                    if *passed { "✅ PASS" } else { "❌ FAIL" },
+                    width = width
                 );
-                return Err(format!("input.json not found in {}", example_dir).into());
             }
-
-            println!(
-                "🧪 Testing program: {} ({})",
-                program,
-                if bootloader { "bootloader" } else { "regular" }
+            info!(
+                "  {} passed, {} failed, {} total",
+                outcomes.len() - failed,
+                failed,
+                outcomes.len()
             );
+            if json {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+            }
 
-            // Copy input.json to root
-            fs::copy(&input_json_path, "input.json")?;
-            println!("  Copied {} to input.json", input_json_path.display());
-
-            // Run forge test
-            let mut cmd = Command::new("forge");
-            cmd.arg("test").arg("--match-test").arg("test_VerifyProof");
-
-            let status = cmd.status()?;
-
-            if status.success() {
-                println!("✅ Test complete");
+            if failed == 0 {
                 Ok(())
             } else {
-                Err("Test failed".into())
+                Err(format!("{} of {} example(s) failed", failed, outcomes.len()).into())
             }
         }
-        Commands::All { gas } => {
-            println!("🧪 Running all Forge tests...");
-
-            let mut cmd = Command::new("forge");
+        Commands::All {
+            gas,
+            gas_report_out,
+            only_failed,
+        } => {
+            let mut cmd = forge_command(cli.forge_bin.as_deref());
             cmd.arg("test");
 
-            if gas {
+            if only_failed {
+                let failures = rerun::load_failures()?;
+                if failures.is_empty() {
+                    println!("✅ No failed tests recorded in {}", rerun::LAST_FAILED_FILE);
+                    return Ok(());
+                }
+                println!("🧪 Re-running {} failed test(s)...", failures.len());
+                let (contracts, tests) = rerun::match_patterns(&failures);
+                cmd.arg("--match-contract")
+                    .arg(contracts)
+                    .arg("--match-test")
+                    .arg(tests);
+            } else {
+                println!("🧪 Running all Forge tests...");
+            }
+
+            if gas || gas_report_out.is_some() {
                 cmd.arg("--gas-report");
                 println!("  (with gas report)");
             }
 
-            let status = cmd.status()?;
+            let passed = match &gas_report_out {
+                Some(path) => capture_gas_report(cmd, path)?,
+                // The gas report is a human-readable table, so stream it unparsed
+                None => {
+                    let (passed, results) = run_forge(cmd, cli.raw || gas)?;
+                    if let Some(results) = results {
+                        rerun::save_failures(&results)?;
+                    }
+                    passed
+                }
+            };
 
-            if status.success() {
+            if passed {
                 println!("✅ All tests passed");
                 Ok(())
             } else {
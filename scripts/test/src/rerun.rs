@@ -0,0 +1,72 @@
+use crate::summary::TestResult;
+use common::read_json_file;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+/// Failing tests of the last parsed `all` run, next to forge's own cache
+pub const LAST_FAILED_FILE: &str = "cache/test-runner-failed.json";
+
+/// Record which of `results` failed, replacing what the last run recorded
+pub fn save_failures(results: &[TestResult]) -> Result<(), Box<dyn std::error::Error>> {
+    let failed: Vec<Value> = results
+        .iter()
+        .filter(|result| !result.passed)
+        .map(|result| json!({ "suite": result.suite, "name": result.name }))
+        .collect();
+    if let Some(dir) = Path::new(LAST_FAILED_FILE).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(
+        LAST_FAILED_FILE,
+        serde_json::to_string_pretty(&json!({ "failed": failed }))? + "\n",
+    )
+    .map_err(|e| format!("Failed to write {}: {}", LAST_FAILED_FILE, e))?;
+    Ok(())
+}
+
+/// `(suite, test)` for every failure the last run recorded
+pub fn load_failures() -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let state: Value = match read_json_file(LAST_FAILED_FILE) {
+        Ok(state) => state,
+        Err(e) if e.is_not_found() => {
+            return Err(format!(
+                "No failed tests recorded in {}; run `test all` first",
+                LAST_FAILED_FILE
+            )
+            .into())
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ok(state
+        .get("failed")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{} has no \"failed\" list", LAST_FAILED_FILE))?
+        .iter()
+        .filter_map(|test| {
+            let suite = test.get("suite")?.as_str()?;
+            let name = test.get("name")?.as_str()?;
+            Some((suite.to_string(), name.to_string()))
+        })
+        .collect())
+}
+
+/// forge `--match-contract` and `--match-test` patterns selecting `failures`. Filters apply
+/// to contracts and tests separately, so a test that shares its name with a failed test in
+/// another failing contract runs too.
+pub fn match_patterns(failures: &[(String, String)]) -> (String, String) {
+    let alternatives = |mut names: Vec<&str>| {
+        names.sort();
+        names.dedup();
+        format!("^({})$", names.join("|"))
+    };
+    let contracts = alternatives(failures.iter().map(|(suite, _)| suite.as_str()).collect());
+    // forge reports tests with their parameter list, e.g. "test_VerifyProof()"
+    let tests = alternatives(
+        failures
+            .iter()
+            .map(|(_, name)| name.split('(').next().unwrap_or(name))
+            .collect(),
+    );
+    (contracts, tests)
+}
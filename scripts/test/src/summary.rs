@@ -0,0 +1,123 @@
+use common::info;
+use serde_json::Value;
+
+/// Result of a single forge test, extracted from `forge test --json`
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub suite: String,
+    pub name: String,
+    pub passed: bool,
+    pub gas: Option<u64>,
+    pub duration: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Parse the JSON emitted by `forge test --json`.
+///
+/// Forge may print compiler output before the JSON document, so the last line that
+/// parses as a JSON object is used.
+pub fn parse_forge_json(stdout: &str) -> Result<Vec<TestResult>, String> {
+    let json: Value = stdout
+        .lines()
+        .rev()
+        .filter(|line| line.trim_start().starts_with('{'))
+        .find_map(|line| serde_json::from_str(line).ok())
+        .ok_or("No JSON test results found in forge output")?;
+
+    let suites = json
+        .as_object()
+        .ok_or("Forge JSON output is not an object")?;
+
+    let mut results = Vec::new();
+    for (suite_path, suite) in suites {
+        // Keys look like "test/CpuVerifier.t.sol:CpuVerifierTest"
+        let suite_name = suite_path
+            .rsplit(':')
+            .next()
+            .unwrap_or(suite_path)
+            .to_string();
+
+        let Some(tests) = suite.get("test_results").and_then(Value::as_object) else {
+            continue;
+        };
+
+        for (name, test) in tests {
+            results.push(TestResult {
+                suite: suite_name.clone(),
+                name: name.clone(),
+                passed: test.get("status").and_then(Value::as_str) == Some("Success"),
+                gas: gas_used(test),
+                duration: test.get("duration").and_then(format_duration),
+                reason: test
+                    .get("reason")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string()),
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Gas for unit tests, mean gas for fuzz tests
+fn gas_used(test: &Value) -> Option<u64> {
+    let kind = test.get("kind")?;
+    kind.get("Unit")
+        .and_then(|unit| unit.get("gas"))
+        .or_else(|| kind.get("Fuzz").and_then(|fuzz| fuzz.get("mean_gas")))
+        .and_then(Value::as_u64)
+}
+
+/// Forge serializes durations either as `{ "secs": .., "nanos": .. }` or as a string
+fn format_duration(duration: &Value) -> Option<String> {
+    if let Some(s) = duration.as_str() {
+        return Some(s.to_string());
+    }
+    let secs = duration.get("secs")?.as_u64()?;
+    let nanos = duration.get("nanos")?.as_u64()?;
+    let millis = secs as f64 * 1000.0 + nanos as f64 / 1_000_000.0;
+    Some(format!("{:.2}ms", millis))
+}
+
+/// Print a compact pass/fail table and return the number of failed tests
+pub fn print_summary(results: &[TestResult]) -> usize {
+    let name_width = results
+        .iter()
+        .map(|r| r.suite.len() + r.name.len() + 2)
+        .max()
+        .unwrap_or(0);
+
+    info!();
+    info!("📋 Test summary:");
+    for result in results {
+        let gas = result
+            .gas
+            .map(|g| g.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let duration = result.duration.as_deref().unwrap_or("-");
+        info!(
+            "  {}  {:<width$}  gas: {:>12}  {:>10}",
+            if result.passed {
+                "✅ PASS"
+            } else {
+                "❌ FAIL"
+            },
+            format!("{}::{}", result.suite, result.name),
+            gas,
+            duration,
+            width = name_width
+        );
+        if let Some(reason) = result.reason.as_deref().filter(|_| !result.passed) {
+            info!("      reason: {}", reason);
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    info!(
+        "  {} passed, {} failed, {} total",
+        results.len() - failed,
+        failed,
+        results.len()
+    );
+    failed
+}
@@ -0,0 +1,150 @@
+use crate::confirm::wait_for_receipt;
+use crate::error::VerifyError;
+use crate::send::{Notice, StepHooks};
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, BlockNumber, TransactionReceipt, U256},
+};
+use std::time::{Duration, Instant};
+
+/// Smallest fee increase nodes accept for a same-nonce replacement (geth's default price bump)
+const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// Resubmit a slow transaction with the same nonce and higher fees
+#[derive(Debug, Clone)]
+pub struct GasBump {
+    /// How long to wait for a transaction to be mined before bumping it
+    pub interval: Duration,
+    /// Fee increase per bump; raised to the replacement minimum if lower
+    pub percent: u64,
+    /// Bumps per step before waiting on the last submission
+    pub max_bumps: usize,
+    /// Highest fee per gas a bump may raise the transaction to, from --max-fee-cap
+    pub fee_cap: Option<U256>,
+}
+
+impl GasBump {
+    fn percent(&self) -> u64 {
+        self.percent.max(MIN_REPLACEMENT_BUMP_PERCENT)
+    }
+}
+
+/// Send `tx` from its `from` account and wait for it, bumping its fees every `bump.interval`
+/// until one of the submissions is mined. Every hash that was broadcast goes to `hooks`.
+pub async fn send_with_bumps<M: Middleware + 'static>(
+    client: &M,
+    mut tx: TypedTransaction,
+    bump: &GasBump,
+    confirmations: usize,
+    name: &str,
+    hooks: &impl StepHooks,
+) -> Result<TransactionReceipt, VerifyError> {
+    // Pin the nonce and fees so every replacement differs only in price
+    if tx.nonce().is_none() {
+        let from = *tx
+            .from()
+            .ok_or_else(|| VerifyError::config(format!("{} has no sender", name)))?;
+        let nonce = client
+            .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(VerifyError::rpc)?;
+        tx.set_nonce(nonce);
+    }
+    client
+        .fill_transaction(&mut tx, None)
+        .await
+        .map_err(VerifyError::rpc)?;
+
+    let first = client
+        .send_transaction(tx.clone(), None)
+        .await
+        .map_err(|e| crate::step_error(name, e))?
+        .tx_hash();
+    hooks.sent(name, first);
+    let mut hashes = vec![first];
+
+    let mut bumps = 0;
+    let mut capped = false;
+    loop {
+        let deadline = Instant::now() + bump.interval;
+        while Instant::now() < deadline {
+            for hash in &hashes {
+                let mined = client
+                    .get_transaction_receipt(*hash)
+                    .await
+                    .map_err(VerifyError::rpc)?;
+                if mined.is_some() {
+                    return wait_for_receipt(client, *hash, confirmations)
+                        .await?
+                        .ok_or_else(|| {
+                            VerifyError::rpc(format!("Transaction receipt not found: {}", name))
+                        });
+                }
+            }
+            tokio::time::sleep(client.provider().get_interval()).await;
+        }
+
+        if bumps == bump.max_bumps {
+            if !capped {
+                capped = true;
+                hooks.notice(name, Notice::BumpsExhausted { bumps });
+            }
+            continue;
+        }
+
+        let mut bumped = tx.clone();
+        bump_fees(&mut bumped, bump.percent());
+        if let Some(cap) = bump.fee_cap.filter(|cap| max_fee_per_gas(&bumped) > *cap) {
+            if !capped {
+                capped = true;
+                hooks.notice(name, Notice::BumpCapped { cap });
+            }
+            continue;
+        }
+        tx = bumped;
+        match client.send_transaction(tx.clone(), None).await {
+            Ok(pending_tx) => {
+                bumps += 1;
+                let hash = pending_tx.tx_hash();
+                hooks.notice(
+                    name,
+                    Notice::Bumped {
+                        hash,
+                        after: bump.interval,
+                        percent: bump.percent(),
+                        bumps,
+                        max_bumps: bump.max_bumps,
+                    },
+                );
+                hooks.sent(name, hash);
+                hashes.push(hash);
+            }
+            // An earlier submission was mined between the last poll and the resend
+            Err(e) if e.to_string().contains("nonce too low") => continue,
+            Err(e) => return Err(crate::step_error(name, e)),
+        }
+    }
+}
+
+/// The most `tx` may pay per gas: its max fee, or its gas price before EIP-1559
+fn max_fee_per_gas(tx: &TypedTransaction) -> U256 {
+    match tx {
+        TypedTransaction::Eip1559(tx) => tx.max_fee_per_gas,
+        TypedTransaction::Legacy(tx) => tx.gas_price,
+        TypedTransaction::Eip2930(tx) => tx.tx.gas_price,
+    }
+    .unwrap_or_default()
+}
+
+/// Raise every fee field on `tx` by `percent`, rounding up so the bump is never zero
+fn bump_fees(tx: &mut TypedTransaction, percent: u64) {
+    let bumped = |fee: U256| fee + (fee * percent + 99) / 100;
+    match tx {
+        TypedTransaction::Eip1559(tx) => {
+            tx.max_fee_per_gas = tx.max_fee_per_gas.map(bumped);
+            tx.max_priority_fee_per_gas = tx.max_priority_fee_per_gas.map(bumped);
+        }
+        TypedTransaction::Legacy(tx) => tx.gas_price = tx.gas_price.map(bumped),
+        TypedTransaction::Eip2930(tx) => tx.tx.gas_price = tx.tx.gas_price.map(bumped),
+    }
+}
@@ -0,0 +1,277 @@
+use crate::error::VerifyError;
+use crate::{
+    annotated_proof_path, contract_lookups, fact_topologies_path, http_options, input_json_path,
+    load_annotated_proof, load_bundle, load_fact_topologies, load_task_metadata, lookup_address,
+    parse_address, Cli,
+};
+use common::transport;
+use common::{
+    load_wallet, load_wallets, read_json_file, resolve_rpc_headers, resolve_rpc_url, Config,
+    ConfigError, HttpOptions, KeySource,
+};
+use ethers::{
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::U256,
+    utils::format_ether,
+};
+use prepare_input::{
+    check_page_counts, prepare_verifier_input_with_topologies, read_verifier_input, VerifierInput,
+};
+use stark_evm_adapter::annotated_proof::AnnotatedProof;
+use std::fmt::Display;
+use verify::{check_trace_keys, split_proof, verify_proof_and_register_calldata};
+
+/// Pass/fail tally of the checks run so far
+#[derive(Default)]
+struct Checklist {
+    passed: usize,
+    failed: usize,
+}
+
+impl Checklist {
+    /// Print `name` with its outcome, returning the value if it passed
+    fn record<T, E: Display>(&mut self, name: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                println!("  ✅ {}", name);
+                self.passed += 1;
+                Some(value)
+            }
+            Err(e) => {
+                println!("  ❌ {}: {}", name, e);
+                self.failed += 1;
+                None
+            }
+        }
+    }
+
+    /// Note a check that couldn't run because one it depends on failed
+    fn skip(&self, name: &str, because: &str) {
+        println!("  ⏭️  {} (skipped: {})", name, because);
+    }
+}
+
+/// Validate everything a run on `network` needs without sending anything: the only RPC call
+/// is one balance lookup for the signing account
+pub async fn run(cli: &Cli, config: &Config, network: &common::Network) -> Result<(), VerifyError> {
+    let mut checks = Checklist::default();
+
+    println!("\n🔎 Endpoint and keys:");
+    let url = checks.record(
+        "RPC URL resolves",
+        resolve_rpc_url(network, cli.rpc_url.as_deref(), config),
+    );
+    if let Some(url) = &url {
+        println!("     {}", url);
+    }
+    let http = checks
+        .record("RPC headers parse", resolve_rpc_headers(&cli.rpc_header))
+        .map(|headers| http_options(cli, headers));
+
+    let wallets = match &cli.keys {
+        Some(keys_path) => checks.record(
+            &format!("Keys file {} parses", keys_path.display()),
+            load_wallets(keys_path),
+        ),
+        None => {
+            let source = if cli.key_stdin {
+                KeySource::Stdin
+            } else {
+                KeySource::Env
+            };
+            checks.record("Private key parses", load_wallet(source).map(|w| vec![w]))
+        }
+    };
+    if let Some(wallets) = &wallets {
+        for wallet in wallets {
+            println!("     {:?}", wallet.address());
+        }
+    }
+
+    match (&url, &http, wallets.as_ref().and_then(|w| w.first())) {
+        (Some(url), Some(http), Some(wallet)) => {
+            if let Some(balance) =
+                checks.record("Account balance", balance(url, http, wallet).await)
+            {
+                println!("     {} ETH", format_ether(balance));
+            }
+        }
+        _ => checks.skip("Account balance", "needs the RPC URL, headers and a key"),
+    }
+
+    println!("\n🔎 Contract addresses:");
+    let bundle = cli
+        .bundle
+        .as_deref()
+        .and_then(|path| checks.record("Proof bundle loads", load_bundle(path)));
+    let deployment = match bundle.as_ref().and_then(|b| b.deployment_addresses.clone()) {
+        Some(deployment) => deployment,
+        // A missing file is fine when env vars or playground.toml provide the addresses
+        None => match read_json_file("deployment-addresses.json") {
+            Ok(deployment) => deployment,
+            Err(e @ ConfigError::Parse { .. }) => checks
+                .record::<serde_json::Value, _>("deployment-addresses.json parses", Err(e))
+                .unwrap_or(serde_json::Value::Null),
+            Err(_) => serde_json::Value::Null,
+        },
+    };
+    let lookups = contract_lookups(cli, config);
+    for lookup in lookups.all() {
+        let resolved = lookup_address(lookup, &deployment)
+            .ok_or_else(|| {
+                format!(
+                    "not in deployment-addresses.json, {} or playground.toml",
+                    lookup.env_var
+                )
+            })
+            .and_then(|(address, source)| {
+                parse_address(lookup.label, &address)
+                    .map(|_| format!("{} (from {})", address, source))
+                    .map_err(|e| e.to_string())
+            });
+        if let Some(description) = checks.record(lookup.label, resolved) {
+            println!("     {}", description);
+        }
+    }
+
+    println!("\n🔎 Proof files:");
+    let annotated_proof = match &bundle {
+        Some(bundle) => checks.record(
+            "Bundled annotated proof parses",
+            serde_json::from_value::<AnnotatedProof>(bundle.annotated_proof.clone()),
+        ),
+        None if cli.bundle.is_some() => None,
+        None => checks.record(
+            "Annotated proof loads",
+            annotated_proof_path(cli, config)
+                .ok_or_else(|| {
+                    VerifyError::config(
+                        "ANNOTATED_PROOF is not set and --annotated-proof was not given",
+                    )
+                })
+                .and_then(|path| load_annotated_proof(&path)),
+        ),
+    };
+    if bundle.is_none() && cli.bundle.is_none() {
+        checks.record(
+            "Fact topologies load",
+            fact_topologies_path(cli, config)
+                .ok_or_else(|| {
+                    VerifyError::config(
+                        "FACT_TOPOLOGIES is not set and --fact-topologies was not given",
+                    )
+                })
+                .and_then(|path| load_fact_topologies(&path)),
+        );
+    }
+    match annotated_proof {
+        Some(annotated_proof) => {
+            if let Some(split) = checks.record("Proof splits", split_proof(annotated_proof)) {
+                let trace_count = checks.record(
+                    "Trace statements are numbered contiguously",
+                    check_trace_keys(split.merkle_statements.keys()),
+                );
+                if let Some(trace_count) = trace_count {
+                    println!(
+                        "     {} trace, {} FRI statements",
+                        trace_count,
+                        split.fri_merkle_statements.len()
+                    );
+                }
+            }
+        }
+        None => checks.skip("Proof splits", "needs the annotated proof"),
+    }
+
+    let input = match (&bundle, input_json_path(cli, config)) {
+        (Some(bundle), _) => Some(bundle.input.clone()),
+        (None, _) if cli.bundle.is_some() => None,
+        (None, Some(input_path)) => checks.record(
+            &format!("Verifier input {} loads", input_path.display()),
+            read_verifier_input(&input_path),
+        ),
+        (None, None) => match (
+            annotated_proof_path(cli, config),
+            fact_topologies_path(cli, config),
+        ) {
+            (Some(proof_path), Some(topologies_path)) => checks.record(
+                "Verifier input prepares from the annotated proof",
+                prepare_verifier_input_with_topologies(&proof_path, &topologies_path),
+            ),
+            _ => None,
+        },
+    };
+    match input {
+        Some(input) => check_input(&mut checks, cli, &input),
+        None => checks.skip("Main proof calldata encodes", "needs the verifier input"),
+    }
+
+    println!("\n{} passed, {} failed", checks.passed, checks.failed);
+    if checks.failed > 0 {
+        return Err(VerifyError::Config(format!(
+            "{} check(s) failed",
+            checks.failed
+        )));
+    }
+    println!("✅ Ready to verify; nothing was sent");
+    Ok(())
+}
+
+/// Look up the balance of `wallet`'s account, failing on an empty one that couldn't pay gas
+async fn balance(url: &str, http: &HttpOptions, wallet: &LocalWallet) -> Result<U256, VerifyError> {
+    let provider = transport::connect(url, http).await?;
+    let balance = provider
+        .get_balance(wallet.address(), None)
+        .await
+        .map_err(|e| VerifyError::Rpc(format!("RPC endpoint {} is unreachable: {}", url, e)))?;
+    if balance.is_zero() {
+        return Err(VerifyError::Config(format!(
+            "{:?} has no funds to pay gas",
+            wallet.address()
+        )));
+    }
+    Ok(balance)
+}
+
+fn check_input(checks: &mut Checklist, cli: &Cli, input: &VerifierInput) {
+    checks.record("Input integrity hash matches", input.check_integrity());
+    let mut input = input.clone();
+    if let Some(path) = &cli.task_metadata {
+        let task_metadata = checks.record(
+            &format!("Task metadata override {} parses", path.display()),
+            load_task_metadata(path),
+        );
+        if let Some(task_metadata) = task_metadata {
+            input.task_metadata = task_metadata;
+        }
+    }
+    let page_mismatches = check_page_counts(&input);
+    checks.record(
+        "Page counts agree across public_input, page facts and task_metadata",
+        if page_mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(page_mismatches.join("; "))
+        },
+    );
+    let cairo_verifier_id = checks.record(
+        "Cairo verifier id resolves",
+        cli.layout
+            .cairo_verifier_id(cli.cairo_verifier_id.map(U256::from)),
+    );
+    match cairo_verifier_id {
+        Some(cairo_verifier_id) => {
+            checks.record(
+                "Main proof calldata encodes",
+                verify_proof_and_register_calldata(
+                    &input,
+                    cairo_verifier_id,
+                    cli.verify_signature.as_deref(),
+                    &cli.verify_arg,
+                ),
+            );
+        }
+        None => checks.skip("Main proof calldata encodes", "needs the Cairo verifier id"),
+    }
+}
@@ -0,0 +1,61 @@
+use crate::error::VerifyError;
+use common::transport::RpcProvider;
+use ethers::{
+    providers::{Middleware, PendingTransaction, ProviderError},
+    types::{TransactionReceipt, TxHash},
+};
+use futures::StreamExt;
+use std::{any::Any, time::Duration};
+
+/// How long to wait before fetching a receipt that came without a status again
+pub const STATUS_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Wait for `hash` to be mined with `confirmations` blocks on top: on each new-block
+/// notification when `client` runs over a WebSocket `Transport`, otherwise by polling at the
+/// provider's interval
+pub async fn wait_for_receipt<M: Middleware + 'static>(
+    client: &M,
+    hash: TxHash,
+    confirmations: usize,
+) -> Result<Option<TransactionReceipt>, ProviderError> {
+    // Only common's transport knows whether it can subscribe; any other client polls
+    let provider: &dyn Any = client.provider();
+    if let Some(provider) = provider
+        .downcast_ref::<RpcProvider>()
+        .filter(|provider| provider.as_ref().is_ws())
+    {
+        let mut blocks = provider.subscribe_blocks().await?;
+        loop {
+            // Checked before every wait so a receipt that raced the subscription isn't missed
+            if let Some(receipt) = provider.get_transaction_receipt(hash).await? {
+                if let Some(mined) = receipt.block_number {
+                    let head = provider.get_block_number().await?;
+                    if head.as_usize() + 1 >= mined.as_usize() + confirmations {
+                        return Ok(Some(receipt));
+                    }
+                }
+            }
+            if blocks.next().await.is_none() {
+                // The subscription ended; finish by polling
+                break;
+            }
+        }
+    }
+
+    PendingTransaction::new(hash, client.provider())
+        .confirmations(confirmations)
+        .await
+}
+
+/// Fetch `hash`'s receipt once more after STATUS_RETRY_DELAY, for a receipt that came without a
+/// status: pre-Byzantium chains and some L2s omit it, or a node may not have filled it in yet
+pub async fn refetch_receipt<M: Middleware>(
+    client: &M,
+    hash: TxHash,
+) -> Result<Option<TransactionReceipt>, VerifyError> {
+    tokio::time::sleep(STATUS_RETRY_DELAY).await;
+    client
+        .get_transaction_receipt(hash)
+        .await
+        .map_err(VerifyError::rpc)
+}
@@ -0,0 +1,127 @@
+use common::transport::RpcProvider;
+use ethers::{
+    contract::ContractError,
+    middleware::{signer::SignerMiddlewareError, Middleware},
+    providers::ProviderError,
+    signers::LocalWallet,
+};
+use std::fmt;
+
+/// Why a verification run failed, by category, so callers can decide to retry or skip
+pub enum VerifyError {
+    /// The RPC endpoint failed or answered unexpectedly
+    Rpc(String),
+    /// A step's transaction reverted or failed on-chain
+    Revert { step: String, reason: String },
+    /// Missing or inconsistent configuration: flags, env vars, addresses, keys, chain id
+    Config(String),
+    /// Reading or writing a local file failed
+    Io(std::io::Error),
+    /// A proof, input, bundle or artifact could not be parsed
+    Parse(String),
+}
+
+impl VerifyError {
+    pub fn rpc(e: impl fmt::Display) -> VerifyError {
+        VerifyError::Rpc(e.to_string())
+    }
+
+    pub fn config(e: impl fmt::Display) -> VerifyError {
+        VerifyError::Config(e.to_string())
+    }
+
+    pub fn parse(e: impl fmt::Display) -> VerifyError {
+        VerifyError::Parse(e.to_string())
+    }
+
+    /// An I/O error with what was being read or written
+    pub fn io(context: impl fmt::Display, e: std::io::Error) -> VerifyError {
+        VerifyError::Io(std::io::Error::new(e.kind(), format!("{}: {}", context, e)))
+    }
+
+    pub fn revert(step: &str, reason: impl fmt::Display) -> VerifyError {
+        VerifyError::Revert {
+            step: step.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Rpc(message) => write!(f, "RPC error: {}", message),
+            VerifyError::Revert { step, reason } => write!(f, "{} failed: {}", step, reason),
+            VerifyError::Config(message) => write!(f, "{}", message),
+            VerifyError::Io(e) => write!(f, "I/O error: {}", e),
+            VerifyError::Parse(message) => write!(f, "Parse error: {}", message),
+        }
+    }
+}
+
+// main() reports its error with Debug, so show the readable message there too
+impl fmt::Debug for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for VerifyError {
+    fn from(e: std::io::Error) -> VerifyError {
+        VerifyError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for VerifyError {
+    fn from(e: serde_json::Error) -> VerifyError {
+        VerifyError::parse(e)
+    }
+}
+
+impl From<ProviderError> for VerifyError {
+    fn from(e: ProviderError) -> VerifyError {
+        VerifyError::rpc(e)
+    }
+}
+
+impl From<SignerMiddlewareError<RpcProvider, LocalWallet>> for VerifyError {
+    fn from(e: SignerMiddlewareError<RpcProvider, LocalWallet>) -> VerifyError {
+        VerifyError::rpc(e)
+    }
+}
+
+/// Failed view calls; reverts of sent steps are reported as `Revert` where the step is known
+impl<M: Middleware> From<ContractError<M>> for VerifyError {
+    fn from(e: ContractError<M>) -> VerifyError {
+        VerifyError::rpc(e)
+    }
+}
+
+/// A JSON file `common` couldn't load, keeping the file's name in the message
+impl From<common::ConfigError> for VerifyError {
+    fn from(e: common::ConfigError) -> VerifyError {
+        match e {
+            common::ConfigError::Io { path, source } => {
+                VerifyError::io(format!("Failed to read {}", path.display()), source)
+            }
+            parse @ common::ConfigError::Parse { .. } => VerifyError::parse(parse),
+        }
+    }
+}
+
+/// The shared helpers in `common` load configuration: the config file, keys, RPC URL and
+/// chain id check
+impl From<Box<dyn std::error::Error>> for VerifyError {
+    fn from(e: Box<dyn std::error::Error>) -> VerifyError {
+        VerifyError::config(e)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::error::VerifyError;
+use ethers::{
+    abi::{decode, ParamType, Token},
+    contract::abigen,
+    providers::Middleware,
+    types::{Address, U256},
+    utils::{id, keccak256},
+};
+use std::sync::Arc;
+
+abigen!(
+    FactRegistry,
+    r#"[
+        function isValid(bytes32 fact) external view returns (bool)
+    ]"#
+);
+
+/// Whether `fact` is registered in the fact registry at `registry`
+pub async fn is_registered<M: Middleware + 'static>(
+    client: Arc<M>,
+    registry: Address,
+    fact: [u8; 32],
+) -> Result<bool, VerifyError> {
+    let registry = FactRegistry::new(registry, client);
+    Ok(registry.is_valid(fact).call().await?)
+}
+
+/// Fact a statement call registers, when it can be told from the calldata alone: only
+/// verifyMerkle's can, FRI statements and pages need more than what they send
+pub fn call_fact(calldata: &[u8]) -> Option<[u8; 32]> {
+    (calldata.get(..4)? == id("verifyMerkle(uint256[],uint256[],uint256,uint256)"))
+        .then(|| merkle_statement_fact(calldata))
+        .flatten()
+}
+
+/// Fact registered by MerkleStatementContract.verifyMerkle: keccak256 of the initial
+/// (index, hash) queue followed by the root, recovered from the call's own calldata
+pub fn merkle_statement_fact(calldata: &[u8]) -> Option<[u8; 32]> {
+    let tokens = decode(
+        &[
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+        ],
+        calldata.get(4..)?,
+    )
+    .ok()?;
+
+    let queue = match &tokens[1] {
+        Token::Array(queue) => queue,
+        _ => return None,
+    };
+    let mut data = Vec::with_capacity((queue.len() + 1) * 32);
+    for token in queue.iter().chain(std::iter::once(&tokens[3])) {
+        data.extend_from_slice(&word(token.clone().into_uint()?));
+    }
+    Some(keccak256(data))
+}
+
+/// `value` as a big-endian ABI word
+pub fn word(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
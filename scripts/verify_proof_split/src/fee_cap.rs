@@ -0,0 +1,67 @@
+use crate::error::VerifyError;
+use ethers::{
+    providers::Middleware,
+    types::{BlockNumber, U256},
+    utils::{format_units, parse_units},
+};
+
+/// Parse a --max-fee-cap value in gwei, fractions allowed
+pub fn parse_gwei(value: &str) -> Result<U256, String> {
+    parse_units(value, "gwei")
+        .map(Into::into)
+        .map_err(|e| format!("invalid gwei amount {:?}: {}", value, e))
+}
+
+pub fn gwei(wei: U256) -> String {
+    format_units(wei, "gwei").unwrap_or_else(|_| wei.to_string())
+}
+
+/// Fail before `name` is sent if the network's fee is above `cap` wei: the higher of the
+/// latest base fee, where the chain has one, and the suggested gas price
+pub async fn check_fee_cap<M: Middleware>(
+    client: &M,
+    cap: U256,
+    name: &str,
+) -> Result<(), VerifyError> {
+    let gas_price = client.get_gas_price().await.map_err(VerifyError::rpc)?;
+    let base_fee = client
+        .get_block(BlockNumber::Latest)
+        .await
+        .map_err(VerifyError::rpc)?
+        .and_then(|block| block.base_fee_per_gas);
+    let (fee, kind) = match base_fee {
+        Some(base_fee) if base_fee > gas_price => (base_fee, "base fee"),
+        _ => (gas_price, "gas price"),
+    };
+    if fee <= cap {
+        return Ok(());
+    }
+    Err(VerifyError::Config(format!(
+        "Not sending {}: the network's {} is {} gwei, above --max-fee-cap {} gwei. Steps \
+         confirmed so far stay registered; rerun once fees drop",
+        name,
+        kind,
+        gwei(fee),
+        gwei(cap)
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_and_fractional_gwei_are_parsed_to_wei() {
+        assert_eq!(parse_gwei("30").unwrap(), U256::from(30_000_000_000u64));
+        assert_eq!(parse_gwei("1.5").unwrap(), U256::from(1_500_000_000u64));
+        assert_eq!(parse_gwei("0.000000001").unwrap(), U256::one());
+    }
+
+    #[test]
+    fn invalid_amounts_are_rejected() {
+        for value in ["abc", "1,5", "30 gwei"] {
+            let err = parse_gwei(value).unwrap_err();
+            assert!(err.contains(&format!("{:?}", value)), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::VerifyError;
+use crate::fee_cap::gwei;
+use common::transport::RpcProvider;
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, BlockNumber, U256},
+    utils::format_units,
+};
+
+/// Estimate every step's gas and print its projected cost under legacy and EIP-1559 pricing,
+/// without sending anything
+pub async fn print_fee_report(
+    provider: &RpcProvider,
+    steps: &[(String, TypedTransaction)],
+) -> Result<(), VerifyError> {
+    let gas_price = provider.get_gas_price().await?;
+    let base_fee = provider
+        .get_block(BlockNumber::Latest)
+        .await?
+        .and_then(|block| block.base_fee_per_gas);
+    let eip1559 = match base_fee {
+        Some(_) => Some(provider.estimate_eip1559_fees(None).await?),
+        None => None,
+    };
+
+    println!("\n⛽ Fee report:");
+    println!("  Gas price (legacy): {} gwei", gwei(gas_price));
+    match (base_fee, eip1559) {
+        (Some(base_fee), Some((max_fee, priority_fee))) => {
+            println!("  Base fee:           {} gwei", gwei(base_fee));
+            println!("  Priority fee:       {} gwei", gwei(priority_fee));
+            println!("  Max fee:            {} gwei", gwei(max_fee));
+        }
+        _ => println!("  Network has no base fee; EIP-1559 pricing is unavailable"),
+    }
+
+    let width = steps.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    println!();
+    let mut total_gas = U256::zero();
+    let mut unestimated = Vec::new();
+    for (name, tx) in steps {
+        match provider.estimate_gas(tx, None).await {
+            Ok(gas) => {
+                total_gas += gas;
+                println!("  {:<width$}  {:>10} gas", name, gas, width = width);
+            }
+            Err(e) => {
+                println!("  {:<width$}  {:>10}", name, "unknown", width = width);
+                eprintln!("    ⚠️  Could not estimate gas: {}", e);
+                unestimated.push(name.as_str());
+            }
+        }
+    }
+
+    println!("\n  Total gas: {}", total_gas);
+    println!(
+        "  Legacy:   {} ETH (gas price {} gwei)",
+        eth(total_gas * gas_price),
+        gwei(gas_price)
+    );
+    if let (Some(base_fee), Some((max_fee, priority_fee))) = (base_fee, eip1559) {
+        // Paid per gas is the base fee plus tip, capped at the max fee
+        let effective = (base_fee + priority_fee).min(max_fee);
+        println!(
+            "  EIP-1559: {} ETH at the current base fee, at most {} ETH",
+            eth(total_gas * effective),
+            eth(total_gas * max_fee)
+        );
+        if effective < gas_price {
+            println!("  💡 EIP-1559 pricing is cheaper at current fees");
+        } else {
+            println!("  💡 Legacy pricing is no more expensive at current fees");
+        }
+    }
+
+    if !unestimated.is_empty() {
+        // The main proof usually can't be estimated until its statements are registered
+        println!(
+            "\n⚠️  {} step(s) could not be estimated and are excluded from the totals: {}",
+            unestimated.len(),
+            unestimated.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub fn eth(wei: U256) -> String {
+    format_units(wei, "ether").unwrap_or_else(|_| wei.to_string())
+}
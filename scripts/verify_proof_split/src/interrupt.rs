@@ -0,0 +1,94 @@
+use ethers::types::TxHash;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+/// How long to let an in-flight send surface its hash after Ctrl-C before giving up on it
+const INTERRUPT_GRACE: Duration = Duration::from_secs(10);
+
+/// Exit code for a run stopped by Ctrl-C (128 + SIGINT)
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+#[derive(Debug, Default)]
+struct Progress {
+    last_completed: Option<String>,
+    /// Every broadcast transaction, and whether it has been confirmed
+    sent: Vec<(String, TxHash, bool)>,
+    reported: bool,
+}
+
+/// Tracks broadcast transactions so a Ctrl-C can report which nonces were consumed
+#[derive(Debug, Clone, Default)]
+pub struct Interrupt {
+    stopping: Arc<AtomicBool>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl Interrupt {
+    /// Install the Ctrl-C handler.
+    ///
+    /// On interrupt no new steps are dispatched; the current step gets `INTERRUPT_GRACE` to
+    /// surface its transaction hash before the report is printed and the process exits.
+    pub fn install() -> Interrupt {
+        let interrupt = Interrupt::default();
+        let handler = interrupt.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            handler.stopping.store(true, Ordering::SeqCst);
+            eprintln!(
+                "\n⚠️  Interrupted: not dispatching further steps, waiting up to {}s for in-flight transactions...",
+                INTERRUPT_GRACE.as_secs()
+            );
+            tokio::time::sleep(INTERRUPT_GRACE).await;
+            handler.report_and_exit();
+        });
+        interrupt
+    }
+
+    /// Stop here if Ctrl-C was pressed; call before dispatching each step
+    pub fn check(&self) {
+        if self.stopping.load(Ordering::SeqCst) {
+            self.report_and_exit();
+        }
+    }
+
+    pub fn sent(&self, name: &str, hash: TxHash) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.sent.push((name.to_string(), hash, false));
+    }
+
+    pub fn completed(&self, name: &str) {
+        let mut progress = self.progress.lock().unwrap();
+        if let Some(entry) = progress.sent.iter_mut().rev().find(|(n, _, _)| n == name) {
+            entry.2 = true;
+        }
+        progress.last_completed = Some(name.to_string());
+    }
+
+    fn report_and_exit(&self) -> ! {
+        let mut progress = self.progress.lock().unwrap();
+        if !progress.reported {
+            progress.reported = true;
+
+            eprintln!("\n🛑 Verification interrupted");
+            eprintln!(
+                "  Last completed step: {}",
+                progress.last_completed.as_deref().unwrap_or("none")
+            );
+            if progress.sent.is_empty() {
+                eprintln!("  No transactions were broadcast");
+            } else {
+                eprintln!("  Broadcast transactions:");
+                for (name, hash, confirmed) in &progress.sent {
+                    let state = if *confirmed { "confirmed" } else { "pending" };
+                    eprintln!("    {} [{}]: {:?}", name, state, hash);
+                }
+            }
+        }
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+}
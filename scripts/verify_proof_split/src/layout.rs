@@ -0,0 +1,77 @@
+use crate::error::VerifyError;
+use clap::ValueEnum;
+use ethers::types::U256;
+
+/// Cairo layout the proof was produced with; picks the Cairo verifier the main proof is sent
+/// to
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// The layout the contracts under src/layout_starknet verify
+    Starknet,
+    Recursive,
+    RecursiveWithPoseidon,
+    Small,
+    Dex,
+}
+
+/// One kind of split statement sent before the main proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Trace decommitment Merkle statements
+    Trace,
+    /// FRI layer statements
+    Fri,
+    /// Continuous memory page registrations
+    Pages,
+}
+
+/// Statement kinds in the order they are sent; the split yields the same kinds for every
+/// layout, and the main proof always runs last since it needs every one of them registered
+pub const STEPS: [Step; 3] = [Step::Trace, Step::Fri, Step::Pages];
+
+impl Step {
+    pub fn heading(self) -> &'static str {
+        match self {
+            Step::Trace => "Verifying trace decommitments:",
+            Step::Fri => "Verifying FRI decommitments:",
+            Step::Pages => "Registering continuous pages:",
+        }
+    }
+
+    /// Short name of the statement kind, as exported bundles label it
+    pub fn kind(self) -> &'static str {
+        match self {
+            Step::Trace => "trace",
+            Step::Fri => "fri",
+            Step::Pages => "pages",
+        }
+    }
+}
+
+impl Layout {
+    /// Index of this layout's CpuVerifier in the GPS verifier's cairoVerifiers, where it is
+    /// known: Deploy.s.sol only registers starknet, at 0. Where other layouts sit depends on
+    /// the deployment that registered them.
+    pub fn default_cairo_verifier_id(self) -> Option<U256> {
+        match self {
+            Layout::Starknet => Some(U256::zero()),
+            Layout::Recursive | Layout::RecursiveWithPoseidon | Layout::Small | Layout::Dex => None,
+        }
+    }
+
+    /// The Cairo verifier id to send the main proof with: `id` when given, otherwise the
+    /// layout's default; layouts without one are an error rather than a guess
+    pub fn cairo_verifier_id(self, id: Option<U256>) -> Result<U256, VerifyError> {
+        id.or_else(|| self.default_cairo_verifier_id())
+            .ok_or_else(|| {
+                VerifyError::Config(format!(
+                    "--layout {} has no default Cairo verifier id (Deploy.s.sol only registers \
+                     starknet); pass --cairo-verifier-id with the index the GPS verifier \
+                     registered it at",
+                    self.to_possible_value()
+                        .map(|value| value.get_name().to_string())
+                        .unwrap_or_else(|| format!("{:?}", self))
+                ))
+            })
+    }
+}
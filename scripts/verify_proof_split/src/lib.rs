@@ -0,0 +1,693 @@
+//! Split STARK proof verification as a library: `verify_split_proof` sends the trace and FRI
+//! statements, the continuous pages and the main proof through any signing middleware, and
+//! returns what each confirmed step cost. The `verify` binary sends the same `Plan` from its
+//! accounts, with keys, networks, progress and reports on top.
+
+pub mod bump;
+pub mod confirm;
+pub mod error;
+pub mod facts;
+pub mod fee_cap;
+pub mod layout;
+pub mod multicall;
+pub mod report;
+pub mod send;
+
+use error::VerifyError;
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, MiddlewareError, Provider},
+    signers::LocalWallet,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest, U256},
+};
+use layout::{Layout, Step, STEPS};
+use multicall::MulticallBatching;
+use prepare_input::{
+    encode_verify_call, encode_verify_proof_and_register, gps_task_facts,
+    prepare_verifier_input_from_proof, verify_proof_and_register_args, FactTopology, VerifierInput,
+};
+use report::StepReceipt;
+use send::{NoHooks, SendOptions};
+use stark_evm_adapter::{
+    annotated_proof::AnnotatedProof,
+    annotation_parser::{split_fri_merkle_statements, SplitProofs},
+};
+use std::{borrow::Cow, collections::BTreeSet, convert::TryFrom, sync::Arc};
+
+/// Placeholder endpoint for --output-calldata, where no request is ever made
+pub const OFFLINE_RPC_URL: &str = "http://127.0.0.1:8545";
+
+/// Step name of the main `verifyProofAndRegister` call, sent after every statement
+pub const MAIN_PROOF: &str = "Main proof";
+
+/// Named statement transactions, in send order
+pub type Statements = Vec<(String, TypedTransaction)>;
+
+/// Addresses of the deployed contracts a verification run calls
+#[derive(Debug, Clone, Copy)]
+pub struct ContractAddresses {
+    pub merkle_statement: Address,
+    pub fri_statement: Address,
+    pub memory_registry: Address,
+    pub gps_verifier: Address,
+}
+
+/// Everything `verify_split_proof` needs, already parsed
+#[derive(Debug, Clone)]
+pub struct VerifyConfig {
+    pub annotated_proof: AnnotatedProof,
+    /// The main proof's input, prepared from `annotated_proof`
+    pub input: VerifierInput,
+    pub addresses: ContractAddresses,
+    pub layout: Layout,
+    /// Overrides the layout's Cairo verifier id; needed for layouts other than starknet
+    pub cairo_verifier_id: Option<U256>,
+    /// Submitted as task_metadata in place of the input's
+    pub task_metadata: Option<Vec<U256>>,
+    /// Signature of the main proof entry point, for verifiers with a non-stock ABI
+    pub verify_signature: Option<String>,
+    /// Values for the parameters `verify_signature` declares after the standard five
+    pub verify_args: Vec<String>,
+    /// Only these FRI statements, in this order; empty sends all of them
+    pub fri_indices: Vec<usize>,
+    pub send: SendOptions,
+}
+
+impl VerifyConfig {
+    /// A starknet-layout run with one confirmation per step
+    pub fn new(
+        annotated_proof: AnnotatedProof,
+        input: VerifierInput,
+        addresses: ContractAddresses,
+    ) -> VerifyConfig {
+        VerifyConfig {
+            annotated_proof,
+            input,
+            addresses,
+            layout: Layout::Starknet,
+            cairo_verifier_id: None,
+            task_metadata: None,
+            verify_signature: None,
+            verify_args: Vec::new(),
+            fri_indices: Vec::new(),
+            send: SendOptions::default(),
+        }
+    }
+
+    /// Like `new`, preparing the input from the proof with task metadata computed from
+    /// `fact_topologies`
+    pub fn from_topologies(
+        annotated_proof: AnnotatedProof,
+        fact_topologies: &[FactTopology],
+        addresses: ContractAddresses,
+    ) -> Result<VerifyConfig, VerifyError> {
+        let input = prepare_verifier_input_from_proof(&annotated_proof, fact_topologies)
+            .map_err(VerifyError::Parse)?;
+        Ok(VerifyConfig::new(annotated_proof, input, addresses))
+    }
+
+    /// The input the main proof is sent with: `input`, with `task_metadata` swapped in when
+    /// set
+    pub fn submitted_input(&self) -> Cow<'_, VerifierInput> {
+        match &self.task_metadata {
+            Some(task_metadata) => {
+                let mut input = self.input.clone();
+                input.task_metadata = task_metadata.clone();
+                Cow::Owned(input)
+            }
+            None => Cow::Borrowed(&self.input),
+        }
+    }
+}
+
+/// Outcome of a `verify_split_proof` run
+#[derive(Debug)]
+pub struct VerifyReport {
+    /// Every confirmed step, in send order
+    pub steps: Vec<StepReceipt>,
+    pub total_gas: U256,
+    /// Whether every step, the main proof last, was confirmed
+    pub success: bool,
+    /// Why the run stopped, when a step failed
+    pub failure: Option<VerifyError>,
+}
+
+impl VerifyReport {
+    fn new(steps: Vec<StepReceipt>, failure: Option<VerifyError>) -> VerifyReport {
+        let total_gas = steps
+            .iter()
+            .filter_map(|step| step.gas_used)
+            .fold(U256::zero(), |sum, gas| sum + gas);
+        VerifyReport {
+            steps,
+            total_gas,
+            success: failure.is_none(),
+            failure,
+        }
+    }
+}
+
+/// Verify `config`'s proof on-chain from `client`'s default sender: every split statement in
+/// send order, one at a time, then the main proof. Nothing is printed; to show progress or
+/// send from several accounts, build a `Plan` and send it with your own `StepHooks`.
+///
+/// Problems found before anything is sent (a malformed proof or input, a client without a
+/// sender) are errors; a step that fails to send or reverts ends the run with a report of the
+/// steps confirmed so far.
+pub async fn verify_split_proof<M: Middleware + 'static>(
+    config: VerifyConfig,
+    client: Arc<M>,
+) -> Result<VerifyReport, VerifyError> {
+    let from = client
+        .default_sender()
+        .ok_or_else(|| VerifyError::config("The client has no default sender to sign with"))?;
+    config.input.check_integrity().map_err(VerifyError::Parse)?;
+    let split_proofs = split_proof(config.annotated_proof.clone())?;
+    let plan = Plan::build(&config, &split_proofs, &[from])?;
+    Ok(plan.send(&config.send, &[client], &NoHooks).await)
+}
+
+/// Every transaction of a run, built up front so it can be sent, estimated or exported
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Each kind of statement, in send order
+    pub statements: Vec<(Step, Statements)>,
+    /// The main proof, sent once every statement is confirmed
+    pub main_proof: Option<TypedTransaction>,
+    /// Facts the main proof registers, checked when its receipt has no status
+    pub task_facts: Vec<[u8; 32]>,
+}
+
+impl Plan {
+    /// The trace, FRI and page statements of `split_proofs` and `config`'s main proof.
+    /// Statements take `senders` round-robin by position in the send order; the main proof
+    /// is sent from the first.
+    pub fn build(
+        config: &VerifyConfig,
+        split_proofs: &SplitProofs,
+        senders: &[Address],
+    ) -> Result<Plan, VerifyError> {
+        let trace_count =
+            check_trace_keys(split_proofs.merkle_statements.keys()).map_err(VerifyError::Parse)?;
+        let fri_indices = select_fri_indices(
+            &config.fri_indices,
+            split_proofs.fri_merkle_statements.len(),
+        )
+        .map_err(VerifyError::Config)?;
+        let builder = offline_builder()?;
+        let addresses = &config.addresses;
+
+        let mut statements = Vec::new();
+        for step in STEPS {
+            let calls: Statements = match step {
+                Step::Trace => (0..trace_count)
+                    .map(|i| {
+                        let key = format!("Trace {}", i);
+                        let call = split_proofs.merkle_statements[&key]
+                            .verify(addresses.merkle_statement, builder.clone());
+                        (key, call.tx)
+                    })
+                    .collect(),
+                Step::Fri => fri_indices
+                    .iter()
+                    .map(|&i| {
+                        let call = split_proofs.fri_merkle_statements[i]
+                            .verify(addresses.fri_statement, builder.clone());
+                        (format!("FRI statement: {}", i), call.tx)
+                    })
+                    .collect(),
+                Step::Pages => page_statements(split_proofs, addresses.memory_registry, &builder),
+            };
+            statements.push((step, calls));
+        }
+
+        let input = config.submitted_input();
+        let call_data = verify_proof_and_register_calldata(
+            &input,
+            config.layout.cairo_verifier_id(config.cairo_verifier_id)?,
+            config.verify_signature.as_deref(),
+            &config.verify_args,
+        )
+        .map_err(VerifyError::Config)?;
+        let mut main_proof = TransactionRequest::new()
+            .to(addresses.gps_verifier)
+            .data(call_data);
+        if let Some(from) = senders.first() {
+            main_proof = main_proof.from(*from);
+        }
+
+        let mut plan = Plan {
+            statements,
+            main_proof: Some(main_proof.into()),
+            task_facts: gps_task_facts(&input).unwrap_or_default(),
+        };
+        plan.assign_senders(senders);
+        Ok(plan)
+    }
+
+    /// Only the continuous page registrations of `split_proofs`, on `memory_registry`; anyone
+    /// can register pages, so they can go ahead of the rest
+    pub fn pages(
+        split_proofs: &SplitProofs,
+        memory_registry: Address,
+        senders: &[Address],
+    ) -> Result<Plan, VerifyError> {
+        let statements = page_statements(split_proofs, memory_registry, &offline_builder()?);
+        let mut plan = Plan {
+            statements: vec![(Step::Pages, statements)],
+            main_proof: None,
+            task_facts: Vec::new(),
+        };
+        plan.assign_senders(senders);
+        Ok(plan)
+    }
+
+    /// Fold the trace and FRI statements into Multicall3 batches; pages and the main proof are
+    /// still sent on their own
+    pub fn batched(mut self, batching: &MulticallBatching) -> Result<Plan, VerifyError> {
+        for (step, statements) in &mut self.statements {
+            if *step != Step::Pages {
+                *statements = multicall::batch(
+                    std::mem::take(statements),
+                    batching.address,
+                    batching.batch_size,
+                    batching.max_calldata,
+                )?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Every transaction, the main proof last, in send order
+    pub fn transactions(&self) -> Statements {
+        let mut transactions: Statements = self
+            .statements
+            .iter()
+            .flat_map(|(_, statements)| statements.iter().cloned())
+            .collect();
+        if let Some(tx) = &self.main_proof {
+            transactions.push((MAIN_PROOF.to_string(), tx.clone()));
+        }
+        transactions
+    }
+
+    /// How many transactions the plan sends
+    pub fn transaction_count(&self) -> usize {
+        self.statements
+            .iter()
+            .map(|(_, statements)| statements.len())
+            .sum::<usize>()
+            + usize::from(self.main_proof.is_some())
+    }
+
+    fn assign_senders(&mut self, senders: &[Address]) {
+        if senders.is_empty() {
+            return;
+        }
+        let statements = self
+            .statements
+            .iter_mut()
+            .flat_map(|(_, statements)| statements.iter_mut());
+        for (position, (_, tx)) in statements.enumerate() {
+            tx.set_from(senders[position % senders.len()]);
+        }
+    }
+}
+
+/// The continuous page registrations of `split_proofs` on `memory_registry`
+fn page_statements(
+    split_proofs: &SplitProofs,
+    memory_registry: Address,
+    builder: &Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+) -> Statements {
+    let (_, continuous_pages) = split_proofs.main_proof.memory_page_registration_args();
+    continuous_pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let call = split_proofs.main_proof.register_continuous_memory_page(
+                memory_registry,
+                builder.clone(),
+                page,
+            );
+            (format!("register continuous page: {}", i), call.tx)
+        })
+        .collect()
+}
+
+/// The adapter builds calls against a signing client; they are only built through this one,
+/// and sent from whichever account the plan assigns
+fn offline_builder() -> Result<Arc<SignerMiddleware<Provider<Http>, LocalWallet>>, VerifyError> {
+    call_builder(LocalWallet::new(&mut ethers::core::rand::thread_rng()))
+}
+
+/// The FRI statement indices to send: all `count` of them, or the `requested` ones in the
+/// order given
+pub fn select_fri_indices(requested: &[usize], count: usize) -> Result<Vec<usize>, String> {
+    if requested.is_empty() {
+        return Ok((0..count).collect());
+    }
+
+    let out_of_range: Vec<String> = requested
+        .iter()
+        .filter(|&&index| index >= count)
+        .map(|index| index.to_string())
+        .collect();
+    if !out_of_range.is_empty() {
+        return Err(format!(
+            "--fri-indices {} out of range; the proof has {} FRI statements (0..{})",
+            out_of_range.join(", "),
+            count,
+            count.saturating_sub(1)
+        ));
+    }
+    let mut seen = BTreeSet::new();
+    if let Some(duplicate) = requested.iter().find(|&&index| !seen.insert(index)) {
+        return Err(format!("--fri-indices lists {} more than once", duplicate));
+    }
+    Ok(requested.to_vec())
+}
+
+/// Split `annotated_proof` into its trace, FRI and main proof parts. The adapter doesn't say
+/// which part it was on when it fails, so the error carries the proof's size, what its
+/// annotations record and where to look.
+pub fn split_proof(annotated_proof: AnnotatedProof) -> Result<SplitProofs, VerifyError> {
+    let proof_hex = &annotated_proof.proof_hex;
+    let proof_bytes = proof_hex.strip_prefix("0x").unwrap_or(proof_hex).len() / 2;
+    let annotations = annotated_proof.annotations.len();
+    let commitments = |pattern: &str| {
+        annotated_proof
+            .annotations
+            .iter()
+            .filter(|line| line.contains(pattern) && line.contains("Commitment: Hash"))
+            .count()
+    };
+    let (trace_commitments, fri_commitments) = (
+        commitments("Commit on Trace"),
+        commitments("/FRI/Commitment/"),
+    );
+
+    split_fri_merkle_statements(annotated_proof).map_err(|e| {
+        let hint = if annotations == 0 {
+            "the proof has no annotations; produce it with cpu_air_verifier's --annotation_file \
+             and --extra_output_file and merge those into the annotated proof"
+        } else if trace_commitments == 0 || fri_commitments == 0 {
+            "the annotations record no trace or FRI commitments; check they are the \
+             `P->V[start:end]: /cpu air/STARK/...` lines cpu_air_verifier writes"
+        } else {
+            "check the annotations come from the same prover run as proof_hex and are complete"
+        };
+        VerifyError::Parse(format!(
+            "Failed to split the annotated proof ({} bytes of proof_hex, {} annotations with \
+             {} trace and {} FRI commitments): {}. Hint: {}",
+            proof_bytes, annotations, trace_commitments, fri_commitments, e, hint
+        ))
+    })
+}
+
+/// A client the adapter can build its statement calls against for `wallet`'s account; it
+/// never makes a request
+pub fn call_builder(
+    wallet: LocalWallet,
+) -> Result<Arc<SignerMiddleware<Provider<Http>, LocalWallet>>, VerifyError> {
+    let offline = Provider::<Http>::try_from(OFFLINE_RPC_URL).map_err(VerifyError::config)?;
+    Ok(Arc::new(SignerMiddleware::new(offline, wallet)))
+}
+
+/// Encode the main `verifyProofAndRegister` call from a prepared verifier input, or the
+/// call `signature` describes when the verifier has a non-stock ABI
+pub fn verify_proof_and_register_calldata(
+    verifier_input: &VerifierInput,
+    cairo_verifier_id: U256,
+    signature: Option<&str>,
+    extra_args: &[String],
+) -> Result<Bytes, String> {
+    // Create cairoAuxInput (public input + z + alpha) - same as test Forge
+    let mut cairo_aux_input = verifier_input.public_input.clone();
+    cairo_aux_input.push(verifier_input.z);
+    cairo_aux_input.push(verifier_input.alpha);
+
+    match signature {
+        None => Ok(encode_verify_proof_and_register(
+            &verifier_input.proof_params,
+            &verifier_input.proof,
+            &verifier_input.task_metadata,
+            &cairo_aux_input,
+            cairo_verifier_id,
+        )),
+        Some(signature) => encode_verify_call(
+            signature,
+            verify_proof_and_register_args(
+                &verifier_input.proof_params,
+                &verifier_input.proof,
+                &verifier_input.task_metadata,
+                &cairo_aux_input,
+                cairo_verifier_id,
+            ),
+            extra_args,
+        ),
+    }
+}
+
+/// Check the trace Merkle statements are keyed exactly "Trace 0".."Trace N-1" and return N,
+/// reporting every missing and unexpected key at once
+pub fn check_trace_keys<'a>(keys: impl Iterator<Item = &'a String>) -> Result<usize, String> {
+    let mut indices = BTreeSet::new();
+    let mut unexpected = Vec::new();
+    let mut count = 0;
+    for key in keys {
+        count += 1;
+        // Only the canonical spelling counts; "Trace 01" would never be looked up
+        match key
+            .strip_prefix("Trace ")
+            .and_then(|index| index.parse::<usize>().ok())
+            .filter(|index| key == &format!("Trace {}", index))
+        {
+            Some(index) => {
+                indices.insert(index);
+            }
+            None => unexpected.push(key.clone()),
+        }
+    }
+
+    let missing: Vec<String> = (0..count)
+        .filter(|index| !indices.contains(index))
+        .map(|index| format!("Trace {}", index))
+        .collect();
+    unexpected.extend(
+        indices
+            .range(count..)
+            .map(|index| format!("Trace {}", index)),
+    );
+    unexpected.sort();
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("missing: {}", missing.join(", ")));
+    }
+    if !unexpected.is_empty() {
+        problems.push(format!("unexpected: {}", unexpected.join(", ")));
+    }
+    if problems.is_empty() {
+        Ok(count)
+    } else {
+        Err(format!(
+            "Expected trace Merkle statements Trace 0..Trace {} ({} statements); {}",
+            count.saturating_sub(1),
+            count,
+            problems.join("; ")
+        ))
+    }
+}
+
+/// A revert is the step's own failure, carrying the revert data; anything else is the RPC
+/// failing to send it
+pub fn step_error(name: &str, e: impl MiddlewareError) -> VerifyError {
+    match e
+        .as_error_response()
+        .and_then(|response| response.as_revert_data())
+    {
+        Some(data) => VerifyError::revert(name, data),
+        None => VerifyError::Rpc(format!("{}: {:?}", name, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::send::StepHooks;
+    use ethers::providers::{JsonRpcError, MockProvider, MockResponse};
+    use ethers::types::{Transaction, TransactionReceipt, TxHash, U64};
+    use std::{sync::Mutex, time::Duration};
+
+    /// A statement already carrying its gas and gas price, so sending it is a single
+    /// eth_sendTransaction
+    fn statement(name: &str) -> (String, TypedTransaction) {
+        let tx = TransactionRequest::new()
+            .to(Address::repeat_byte(0x11))
+            .data(vec![0xde, 0xad])
+            .gas(100_000)
+            .gas_price(1);
+        (name.to_string(), tx.into())
+    }
+
+    fn trace_plan(names: &[&str]) -> Plan {
+        Plan {
+            statements: vec![(
+                Step::Trace,
+                names.iter().map(|name| statement(name)).collect(),
+            )],
+            main_proof: None,
+            task_facts: Vec::new(),
+        }
+    }
+
+    /// A client over a mocked endpoint, polling fast enough for tests
+    fn mocked_client() -> (Arc<Provider<MockProvider>>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        let provider = provider
+            .with_sender(Address::repeat_byte(0xaa))
+            .interval(Duration::from_millis(1));
+        (Arc::new(provider), mock)
+    }
+
+    /// Every hook call, as "<hook> <step>"
+    #[derive(Default)]
+    struct RecordingHooks(Mutex<Vec<String>>);
+
+    impl RecordingHooks {
+        fn record(&self, hook: &str, name: &str) {
+            self.0.lock().unwrap().push(format!("{} {}", hook, name));
+        }
+    }
+
+    impl StepHooks for RecordingHooks {
+        fn sent(&self, name: &str, _hash: TxHash) {
+            self.record("sent", name);
+        }
+
+        fn confirmed(&self, name: &str, _receipt: &TransactionReceipt) {
+            self.record("confirmed", name);
+        }
+
+        fn failed(&self, name: &str, _error: &VerifyError) {
+            self.record("failed", name);
+        }
+    }
+
+    fn trace_keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn contiguous_trace_keys_are_counted_in_any_order() {
+        let keys = trace_keys(&["Trace 2", "Trace 0", "Trace 1"]);
+        assert_eq!(check_trace_keys(keys.iter()), Ok(3));
+    }
+
+    #[test]
+    fn missing_trace_key_is_reported() {
+        let keys = trace_keys(&["Trace 0", "Trace 2"]);
+        let error = check_trace_keys(keys.iter()).unwrap_err();
+        assert!(error.contains("missing: Trace 1"), "{}", error);
+        assert!(error.contains("unexpected: Trace 2"), "{}", error);
+
+        let keys = trace_keys(&["Trace 0", "Trace 01"]);
+        let error = check_trace_keys(keys.iter()).unwrap_err();
+        assert!(error.contains("missing: Trace 1"), "{}", error);
+        assert!(error.contains("unexpected: Trace 01"), "{}", error);
+    }
+
+    #[test]
+    fn empty_fri_selection_sends_every_statement() {
+        assert_eq!(select_fri_indices(&[], 4), Ok(vec![0, 1, 2, 3]));
+        assert_eq!(select_fri_indices(&[], 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn fri_selection_keeps_the_requested_order() {
+        assert_eq!(select_fri_indices(&[3, 0], 4), Ok(vec![3, 0]));
+    }
+
+    #[test]
+    fn out_of_range_fri_indices_are_listed() {
+        let error = select_fri_indices(&[1, 4, 7], 4).unwrap_err();
+        assert!(
+            error.contains("--fri-indices 4, 7 out of range"),
+            "{}",
+            error
+        );
+        assert!(error.contains("4 FRI statements (0..3)"), "{}", error);
+    }
+
+    #[test]
+    fn duplicate_fri_index_is_rejected() {
+        let error = select_fri_indices(&[2, 1, 2], 4).unwrap_err();
+        assert_eq!(error, "--fri-indices lists 2 more than once");
+    }
+
+    #[tokio::test]
+    async fn confirmed_statement_is_reported_with_its_receipt() {
+        let (client, mock) = mocked_client();
+        let hash = TxHash::repeat_byte(0x42);
+        // The mock answers from the last response pushed: the send, the pending transaction
+        // lookup, then its receipt
+        mock.push(TransactionReceipt {
+            transaction_hash: hash,
+            block_number: Some(U64::from(7)),
+            gas_used: Some(U256::from(50_000)),
+            status: Some(U64::from(1)),
+            ..Default::default()
+        })
+        .unwrap();
+        mock.push(Transaction {
+            hash,
+            block_number: Some(U64::from(7)),
+            ..Default::default()
+        })
+        .unwrap();
+        mock.push(hash).unwrap();
+
+        let hooks = RecordingHooks::default();
+        let report = trace_plan(&["Trace 0"])
+            .send(&SendOptions::default(), &[client], &hooks)
+            .await;
+
+        assert!(report.success, "{:?}", report.failure);
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(report.steps[0].name, "Trace 0");
+        assert_eq!(report.steps[0].tx_hash, hash);
+        assert_eq!(report.total_gas, U256::from(50_000));
+        assert_eq!(
+            *hooks.0.lock().unwrap(),
+            ["sent Trace 0", "confirmed Trace 0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn reverted_statement_ends_the_run_with_its_revert_data() {
+        let (client, mock) = mocked_client();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(serde_json::json!("0xdeadbeef")),
+        }));
+
+        let hooks = RecordingHooks::default();
+        let report = trace_plan(&["Trace 0", "Trace 1"])
+            .send(&SendOptions::default(), &[client], &hooks)
+            .await;
+
+        assert!(!report.success);
+        assert!(report.steps.is_empty());
+        match report.failure {
+            Some(VerifyError::Revert { step, reason }) => {
+                assert_eq!(step, "Trace 0");
+                assert_eq!(reason, "0xdeadbeef");
+            }
+            other => panic!("expected a revert, got {:?}", other),
+        }
+        // Trace 1 was never sent
+        assert_eq!(*hooks.0.lock().unwrap(), ["failed Trace 0"]);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::VerifyError;
+use common::deployment::{
+    deployed_contracts, deployment_addresses, latest_broadcast_file, DEPLOY_SCRIPT,
+};
+use common::{forge_command, spawn_error};
+use ethers::providers::{Http, Middleware, Provider};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+/// Chain id anvil reports by default
+pub const ANVIL_CHAIN_ID: u64 = 31337;
+
+/// Private key of anvil's first prefunded account, used when PRIVATE_KEY is not set
+pub const ANVIL_PRIVATE_KEY: &str =
+    "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+/// Block gas limit for the local chain, high enough for the main proof in one transaction
+const ANVIL_GAS_LIMIT: &str = "100000000";
+
+/// How long to wait for anvil to start answering RPC requests
+const ANVIL_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A local anvil node, killed when dropped
+pub struct Anvil {
+    child: Child,
+    pub url: String,
+}
+
+impl Anvil {
+    pub async fn start(anvil_bin: &str, port: u16) -> Result<Anvil, VerifyError> {
+        println!("⛓️  Starting anvil on port {}...", port);
+        let mut cmd = Command::new(anvil_bin);
+        cmd.arg("--port")
+            .arg(port.to_string())
+            .arg("--gas-limit")
+            .arg(ANVIL_GAS_LIMIT)
+            .arg("--silent")
+            .stdout(Stdio::null());
+        let child = cmd.spawn().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                VerifyError::Config(format!(
+                    "anvil not found (tried '{}'). Install Foundry or pass --anvil-bin",
+                    anvil_bin
+                ))
+            } else {
+                VerifyError::io("Failed to start anvil", e)
+            }
+        })?;
+
+        let mut anvil = Anvil {
+            child,
+            url: format!("http://127.0.0.1:{}", port),
+        };
+        anvil.wait_until_ready().await?;
+        Ok(anvil)
+    }
+
+    async fn wait_until_ready(&mut self) -> Result<(), VerifyError> {
+        let provider: Provider<Http> =
+            Provider::try_from(self.url.as_str()).map_err(VerifyError::config)?;
+        let started = std::time::Instant::now();
+        loop {
+            if provider.get_chainid().await.is_ok() {
+                println!("  ✅ anvil is up at {}", self.url);
+                return Ok(());
+            }
+            if let Some(status) = self.child.try_wait()? {
+                return Err(VerifyError::Config(format!(
+                    "anvil exited during startup ({})",
+                    status
+                )));
+            }
+            if started.elapsed() > ANVIL_STARTUP_TIMEOUT {
+                return Err(VerifyError::Rpc(format!(
+                    "anvil did not answer on {} within {}s",
+                    self.url,
+                    ANVIL_STARTUP_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        println!("🧹 Stopped anvil");
+    }
+}
+
+/// Run the deploy script against `rpc_url` and return the deployed addresses in
+/// deployment-addresses.json form, without touching the file itself
+pub fn deploy(forge_bin: Option<&str>, rpc_url: &str) -> Result<serde_json::Value, VerifyError> {
+    println!("🚀 Deploying contracts to anvil...");
+    let mut cmd = forge_command(forge_bin);
+    cmd.arg("script")
+        .arg(DEPLOY_SCRIPT)
+        .arg("--rpc-url")
+        .arg(rpc_url)
+        .arg("--broadcast")
+        .stdout(Stdio::null());
+    let status = cmd.status().map_err(|e| spawn_error(&cmd, e))?;
+    if !status.success() {
+        return Err(VerifyError::config("Deploying to anvil failed"));
+    }
+
+    let broadcast = latest_broadcast_file()
+        .ok_or_else(|| VerifyError::config("Broadcast file not found under broadcast/"))?;
+    let contracts = deployed_contracts(&broadcast)?;
+    println!("  ✅ Deployed {} contracts", contracts.len());
+    Ok(deployment_addresses(&contracts))
+}
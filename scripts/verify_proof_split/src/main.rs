@@ -1,40 +1,300 @@
+mod check;
+mod fee_report;
+mod interrupt;
+mod local;
+mod metrics;
+mod plan;
+mod progress;
+mod repeat;
+mod simulate;
+mod spend;
+mod status;
+mod watch;
+
+use bump::GasBump;
 use clap::{Parser, Subcommand};
+use common::transport::{self, RpcProvider, Transport};
+use common::{
+    check_chain_id, load_env_file, load_signer, load_signers, read_json_file, resolve_forge_bin,
+    resolve_rpc_headers, resolve_rpc_url, Config, ConfigError, HttpOptions, KeySource,
+};
+use error::VerifyError;
 use ethers::{
-    contract::ContractError,
-    core::k256::ecdsa::SigningKey,
+    core::rand::thread_rng,
     middleware::SignerMiddleware,
-    providers::{Http, Middleware, Provider},
-    signers::{LocalWallet, Signer, Wallet},
-    types::{Address, U256, U64},
-    utils::hex,
+    providers::{Http, Middleware, Provider, ProviderError},
+    signers::LocalWallet,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, TransactionReceipt, TxHash, U256,
+        U64,
+    },
+};
+use fee_cap::{gwei, parse_gwei};
+use fee_report::print_fee_report;
+use interrupt::Interrupt;
+use layout::{Layout, Step};
+use local::{Anvil, ANVIL_CHAIN_ID, ANVIL_PRIVATE_KEY};
+use metrics::RunMetrics;
+use multicall::MulticallBatching;
+use plan::print_plan;
+use prepare_input::{
+    check_page_counts, prepare_verifier_input_with_topologies, read_verifier_input, ProofBundle,
 };
-use prepare_input::prepare_verifier_input;
-use stark_evm_adapter::{
-    annotated_proof::AnnotatedProof, annotation_parser::split_fri_merkle_statements,
-    oods_statement::FactTopology, ContractFunctionCall,
+use progress::StepProgress;
+use repeat::{print_repeat_summary, RunSample};
+use report::{Report, StepReceipt};
+use send::{Notice, SendOptions, Stage, StepHooks};
+use simulate::simulate;
+use spend::confirm_spend;
+use stark_evm_adapter::{annotated_proof::AnnotatedProof, oods_statement::FactTopology};
+use status::{check_prerequisites, print_status, StatusContracts};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::{convert::TryFrom, env, fs::read_to_string, str::FromStr, sync::Arc};
+use verify::{
+    bump, error, facts, fee_cap, layout, multicall, report, send, split_proof, ContractAddresses,
+    Plan, VerifyConfig, MAIN_PROOF, OFFLINE_RPC_URL,
+};
+
+/// Default --max-calldata-size: the most calldata a 30M gas block can hold after the base
+/// transaction cost, at 16 gas per (nonzero) byte
+const DEFAULT_MAX_CALLDATA_SIZE: usize = (30_000_000 - 21_000) / 16;
 
 #[derive(Parser, Debug)]
-#[command(name = "verify")]
+#[command(name = "verify", version = env!("BUILD_VERSION"))]
 #[command(about = "Verify large STARK proofs by splitting them into smaller transactions")]
 struct Cli {
     /// Path to annotated_proof.json file
     #[arg(short, long)]
     annotated_proof: Option<String>,
 
-    /// Path to input.json file (for main proof verification)
+    /// Path to a prepared input.json, or input.cbor from `prepare-input --format cbor`, for
+    /// the main proof (defaults to paths.input_json in playground.toml; prepared from the
+    /// annotated proof if neither is set)
     #[arg(short, long)]
-    input_json: Option<String>,
+    input_json: Option<PathBuf>,
+
+    /// JSON array of hex words to submit as task_metadata in place of the input's, e.g. for
+    /// a bootloader configuration the prepared input doesn't match
+    #[arg(long, value_name = "PATH")]
+    task_metadata: Option<PathBuf>,
 
     /// Path to fact_topologies.json file
     #[arg(short, long)]
     fact_topologies: Option<String>,
 
+    /// Path to a proof bundle from `prepare-input bundle` (replaces the individual files)
+    #[arg(long, conflicts_with_all = ["annotated_proof", "input_json", "fact_topologies"])]
+    bundle: Option<String>,
+
     /// RPC URL for Ethereum network (overrides network default and env vars)
     #[arg(short, long)]
     rpc_url: Option<String>,
 
+    /// Extra HTTP header for every RPC request, as KEY=VALUE (repeatable; defaults to the
+    /// comma-separated pairs in RPC_HEADERS)
+    #[arg(long, value_name = "KEY=VALUE")]
+    rpc_header: Vec<String>,
+
+    /// How often to poll the RPC endpoint for receipts, in milliseconds (ethers' default is
+    /// 7000 for remote endpoints; raise it for rate-limited ones, lower it for fast chains)
+    #[arg(long, value_name = "MS")]
+    poll_interval: Option<u64>,
+
+    /// Fail any HTTP RPC request that takes longer than this many seconds, instead of hanging
+    /// on a stalled endpoint
+    #[arg(long, value_name = "SECS")]
+    http_timeout: Option<u64>,
+
+    /// Idle HTTP connections kept open for reuse (reqwest's default keeps every one)
+    #[arg(long, value_name = "N")]
+    http_pool_max_idle: Option<usize>,
+
+    /// Seconds an idle HTTP connection is kept before it's closed (reqwest's default is 90)
+    #[arg(long, value_name = "SECS")]
+    http_pool_idle_timeout: Option<u64>,
+
+    /// GPS verifier address (overrides deployment-addresses.json and GPS_VERIFIER_ADDRESS)
+    #[arg(long, value_name = "ADDRESS")]
+    gps_verifier: Option<String>,
+
+    /// Merkle statement contract address (overrides deployment-addresses.json and MERKLE_STATEMENT_ADDRESS)
+    #[arg(long, value_name = "ADDRESS")]
+    merkle_statement: Option<String>,
+
+    /// FRI statement contract address (overrides deployment-addresses.json and FRI_STATEMENT_ADDRESS)
+    #[arg(long, value_name = "ADDRESS")]
+    fri_statement: Option<String>,
+
+    /// Memory page fact registry address (overrides deployment-addresses.json and MEMORY_REGISTRY_ADDRESS)
+    #[arg(long, value_name = "ADDRESS")]
+    fact_registry: Option<String>,
+
+    /// Only send these FRI statements (comma-separated indices), e.g. to check a layer
+    /// suspected of failing on-chain; the main proof still needs all of them registered
+    #[arg(long, value_name = "INDICES", value_delimiter = ',')]
+    fri_indices: Vec<usize>,
+
+    /// Send only the main proof, for when every trace, FRI and page statement is already
+    /// registered (by an earlier run or someone else); their facts are looked up first and
+    /// nothing is sent if any is missing
+    #[arg(long, conflicts_with_all = ["fri_indices", "multicall", "status", "output_calldata", "repeat"])]
+    only_main: bool,
+
+    /// Multicall3 contract to batch the trace and FRI statements through, cutting the
+    /// per-transaction overhead (deployed at 0xcA11bde05977b3631167028862bE2a173976CA11 on
+    /// most networks; pages and the main proof are still sent on their own)
+    #[arg(long, value_name = "ADDRESS")]
+    multicall: Option<String>,
+
+    /// Most statements per multicall transaction (batches also stay under
+    /// --max-calldata-size)
+    #[arg(long, value_name = "N", default_value_t = 10, requires = "multicall")]
+    multicall_batch: usize,
+
+    /// Cairo layout the proof was produced with; picks the Cairo verifier id (layouts other
+    /// than starknet need --cairo-verifier-id)
+    #[arg(long, value_enum, default_value_t = Layout::Starknet)]
+    layout: Layout,
+
+    /// Index of the Cairo verifier in the GPS verifier's cairoVerifiers (defaults to 0 for
+    /// --layout starknet)
+    #[arg(long, value_name = "ID")]
+    cairo_verifier_id: Option<u64>,
+
+    /// Signature of the main proof entry point, for verifiers behind a proxy with a different
+    /// ABI (defaults to verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256))
+    #[arg(long, value_name = "SIGNATURE")]
+    verify_signature: Option<String>,
+
+    /// Value for each parameter --verify-signature declares after the standard five
+    /// (repeatable, in order)
+    #[arg(long, value_name = "VALUE", requires = "verify_signature")]
+    verify_arg: Vec<String>,
+
+    /// Chain id the RPC endpoint must report (defaults to the selected network's)
+    #[arg(long)]
+    expected_chain_id: Option<u64>,
+
+    /// File with one private key per line; statements are spread across these accounts and
+    /// submitted in parallel (PRIVATE_KEY is used when omitted)
+    #[arg(long, value_name = "FILE", conflicts_with = "output_calldata")]
+    keys: Option<PathBuf>,
+
+    /// Read the hex private key from one line on stdin instead of PRIVATE_KEY_FILE / PRIVATE_KEY
+    #[arg(long, conflicts_with = "keys")]
+    key_stdin: bool,
+
+    /// Resubmit a transaction with the same nonce and higher fees if it isn't mined within
+    /// this many seconds
+    #[arg(long, value_name = "SECS", conflicts_with_all = ["output_calldata", "status"])]
+    bump_interval: Option<u64>,
+
+    /// Fee increase per bump in percent (at least the 10% nodes require for a replacement)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        requires = "bump_interval"
+    )]
+    bump_percent: u64,
+
+    /// Most bumps per transaction; after that the last submission is waited on
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        requires = "bump_interval"
+    )]
+    max_bumps: usize,
+
+    /// Abort instead of sending any step while the network's base fee or gas price is above
+    /// this many gwei; fee bumps also stop short of it
+    #[arg(long, value_name = "GWEI", value_parser = parse_gwei)]
+    max_fee_cap: Option<U256>,
+
+    /// Warn about any step whose calldata is larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_CALLDATA_SIZE)]
+    max_calldata_size: usize,
+
+    /// Fail instead of warning when a step exceeds --max-calldata-size or the main proof's
+    /// page counts disagree
+    #[arg(long)]
+    strict: bool,
+
+    /// Send without asking first; otherwise a run that would spend funds on a non-local chain
+    /// prints the transaction count and estimated cost and waits for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Don't draw the progress bar; print a line per confirmed step instead
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Write a JSON report of every confirmed step (tx hash, gas, registered page fact hash)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["output_calldata", "status"])]
+    report: Option<PathBuf>,
+
+    /// Write Prometheus textfile metrics for the run (step counts, gas, duration), also on
+    /// failure
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["output_calldata", "status"])]
+    metrics_out: Option<PathBuf>,
+
+    /// Print which statements, pages and task facts are already registered, without sending
+    #[arg(long, conflicts_with_all = ["output_calldata", "keys"])]
+    status: bool,
+
+    /// Print every step's calldata size and estimated gas before sending anything
+    #[arg(long, conflicts_with_all = ["output_calldata", "status"])]
+    plan: bool,
+
+    /// Estimate each step's gas and print the projected cost under legacy and EIP-1559
+    /// pricing, without sending
+    #[arg(long, conflicts_with_all = ["output_calldata", "status", "keys", "report", "metrics_out", "bump_interval", "plan"])]
+    fee_report: bool,
+
+    /// Run every step as an eth_call against the latest block instead of sending it, stopping
+    /// at the first one that would revert
+    #[arg(long, conflicts_with_all = ["output_calldata", "status", "keys", "report", "metrics_out", "bump_interval", "fee_report"])]
+    simulate: bool,
+
+    /// With --simulate, keep going after a step would revert and list every failure with its
+    /// revert reason at the end
+    #[arg(long, requires = "simulate")]
+    collect_failures: bool,
+
+    /// Write each step's transaction request to this directory instead of sending it
+    #[arg(long, value_name = "DIR")]
+    output_calldata: Option<PathBuf>,
+
+    /// With --output-calldata, write the whole sequence as one JSON array of transactions,
+    /// DIR/transactions.json, each with its order and what it depends on, for a batch submitter
+    #[arg(long, requires = "output_calldata")]
+    calldata_bundle: bool,
+
+    /// Run the full verification this many times and print per-run and aggregate time and gas
+    /// statistics. For benchmarking on local and dev chains, not testnets
+    #[arg(long, value_name = "N", conflicts_with_all = ["output_calldata", "status", "fee_report", "simulate", "watch"])]
+    repeat: Option<usize>,
+
+    /// Re-run the --simulate dry run whenever the annotated proof, input, fact topologies or
+    /// bundle changes, printing whether every step would pass; nothing is sent
+    #[arg(long, conflicts_with_all = ["output_calldata", "status", "plan", "fee_report", "report", "metrics_out", "keys", "bump_interval"])]
+    watch: bool,
+
+    /// Path to a playground.toml config file (defaults to ./playground.toml if present)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Load env vars from this dotenv file (e.g. .env.staging) before reading any, over
+    /// those already set in the shell
+    #[arg(long, value_name = "PATH")]
+    env_file: Option<PathBuf>,
+
     #[command(subcommand)]
     network: Option<Network>,
 }
@@ -45,269 +305,1129 @@ enum Network {
     Sepolia,
     /// Verify on Base Sepolia testnet
     BaseSepolia,
+    /// Start anvil, deploy the contracts to it and verify end-to-end, then stop anvil
+    Local {
+        /// Port for anvil to listen on
+        #[arg(long, default_value_t = 8545)]
+        port: u16,
+
+        /// Path to the anvil binary
+        #[arg(long, default_value = "anvil")]
+        anvil_bin: String,
+
+        /// Path to the forge binary (overrides FORGE_BIN, defaults to `forge` on PATH)
+        #[arg(long)]
+        forge_bin: Option<String>,
+    },
+    /// Only register the continuous memory pages, then exit; anyone can register pages, so a
+    /// dedicated account can do this ahead of the main verification
+    Pages {
+        #[command(subcommand)]
+        network: Option<RemoteNetwork>,
+    },
+    /// Check the RPC endpoint, keys, contract addresses and proof files without sending
+    /// anything; the only RPC call is one balance lookup
+    Check {
+        #[command(subcommand)]
+        network: Option<RemoteNetwork>,
+    },
+}
+
+/// Networks `verify pages` and `verify check` can target (Sepolia by default)
+#[derive(Subcommand, Debug)]
+enum RemoteNetwork {
+    /// Sepolia testnet
+    Sepolia,
+    /// Base Sepolia testnet
+    BaseSepolia,
+}
+
+impl Network {
+    fn network(&self) -> common::Network {
+        match self {
+            Network::Sepolia => common::Network::Sepolia,
+            Network::BaseSepolia => common::Network::BaseSepolia,
+            // The anvil URL is always passed explicitly
+            Network::Local { .. } => common::Network::Custom {
+                rpc_env: "RPC_URL".to_string(),
+            },
+            Network::Pages {
+                network: Some(RemoteNetwork::BaseSepolia),
+            }
+            | Network::Check {
+                network: Some(RemoteNetwork::BaseSepolia),
+            } => common::Network::BaseSepolia,
+            Network::Pages { .. } | Network::Check { .. } => common::Network::Sepolia,
+        }
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Note: Use direnv to load environment variables from .env
-    // direnv will automatically load them into the shell, and env::var() will see them
+async fn main() -> Result<(), VerifyError> {
+    // Note: Use direnv to load environment variables from .env, or pick a file with
+    // --env-file; either way env::var() sees them
 
+    let started = Instant::now();
     let cli = Cli::parse();
+    println!("verify {}", env!("BUILD_VERSION"));
+    if let Some(env_file) = &cli.env_file {
+        load_env_file(env_file)?;
+    }
+    let config = Config::load(cli.config.as_deref())?;
+    if cli.watch {
+        if matches!(
+            cli.network,
+            Some(Network::Pages { .. } | Network::Check { .. })
+        ) {
+            return Err(VerifyError::config(
+                "--watch re-runs the --simulate dry run of the full verification; drop `pages` or `check`",
+            ));
+        }
+        // Each run is a --simulate dry run, so edits are only ever checked with eth_call
+        let mut args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--watch").collect();
+        if !cli.simulate {
+            args.insert(0, "--simulate".to_string());
+        }
+        return watch::run(&watched_files(&cli, &config), &args);
+    }
+    let send_options = SendOptions {
+        confirmations: config.confirmations.unwrap_or(1),
+        bump: cli.bump_interval.map(|secs| GasBump {
+            interval: Duration::from_secs(secs),
+            percent: cli.bump_percent,
+            max_bumps: cli.max_bumps,
+            fee_cap: cli.max_fee_cap,
+        }),
+        fee_cap: cli.max_fee_cap,
+    };
+    let pages_only = matches!(cli.network, Some(Network::Pages { .. }));
+    if pages_only
+        && (cli.status
+            || cli.fee_report
+            || cli.plan
+            || cli.simulate
+            || cli.repeat.is_some()
+            || cli.output_calldata.is_some()
+            || cli.only_main)
+    {
+        return Err(VerifyError::config(
+            "`verify pages` only registers pages; --status, --fee-report, --plan, --simulate, --repeat, --output-calldata and --only-main cover the full run",
+        ));
+    }
+    if cli.repeat == Some(0) {
+        return Err(VerifyError::config("--repeat needs at least one run"));
+    }
 
-    // Load RPC URL - prioritize explicit --rpc-url, then network subcommand, then env vars
-    let url = cli.rpc_url.or_else(|| {
-        match &cli.network {
-            Some(Network::Sepolia) => env::var("SEPOLIA_RPC_URL").ok(),
-            Some(Network::BaseSepolia) => env::var("BASE_SEPOLIA_RPC_URL").ok(),
-            None => env::var("SEPOLIA_RPC_URL").ok(),
+    // Load RPC URL - prioritize explicit --rpc-url, then network subcommand (Sepolia by default)
+    let network = cli
+        .network
+        .as_ref()
+        .map(Network::network)
+        .unwrap_or(common::Network::Sepolia);
+    if matches!(cli.network, Some(Network::Check { .. })) {
+        return check::run(&cli, &config, &network).await;
+    }
+
+    // Local mode: a fresh anvil chain with freshly deployed contracts, stopped on exit
+    let mut local_deployment = None;
+    let anvil = match &cli.network {
+        Some(Network::Local {
+            port,
+            anvil_bin,
+            forge_bin,
+        }) => {
+            let anvil = Anvil::start(anvil_bin, *port).await?;
+            if env::var("PRIVATE_KEY").is_err() {
+                // Deploy script and signer both read PRIVATE_KEY
+                env::set_var("PRIVATE_KEY", ANVIL_PRIVATE_KEY);
+            }
+            let forge_bin = resolve_forge_bin(forge_bin.clone(), &config);
+            local_deployment = Some(local::deploy(forge_bin.as_deref(), &anvil.url)?);
+            Some(anvil)
         }
-    }).expect("RPC URL must be set via --rpc-url, network subcommand (sepolia/base-sepolia), or SEPOLIA_RPC_URL env var");
+        _ => None,
+    };
+    if cli.repeat.is_some() {
+        if anvil.is_none() && network.chain_id().is_some() {
+            return Err(VerifyError::config(
+                "--repeat is for benchmarking on local and dev chains; use `verify local` or --rpc-url with a custom network",
+            ));
+        }
+        eprintln!("⚠️  --repeat sends the full verification every run; meant for local and dev chains only");
+    }
+    let expected_chain_id = match &anvil {
+        Some(_) => Some(ANVIL_CHAIN_ID),
+        None => cli.expected_chain_id.or_else(|| network.chain_id()),
+    };
 
-    println!("Using RPC URL: {}", url);
-    let provider: Provider<Http> = Provider::try_from(url.as_str())?;
+    // The HTTP endpoint, kept for the batched --status lookups
+    let mut endpoint: Option<(String, HttpOptions)> = None;
+    let provider: RpcProvider = if cli.output_calldata.is_some() {
+        // Calls are only encoded, never sent, so no RPC endpoint is needed
+        Provider::new(Transport::Http(
+            Http::from_str(OFFLINE_RPC_URL).map_err(VerifyError::config)?,
+        ))
+    } else {
+        let rpc_url_override = anvil
+            .as_ref()
+            .map(|a| a.url.as_str())
+            .or(cli.rpc_url.as_deref());
+        let url = resolve_rpc_url(&network, rpc_url_override, &config)?;
 
-    let private_key = env::var("PRIVATE_KEY").expect("PRIVATE_KEY must be set in .env");
-    let from_key_bytes = hex::decode(private_key.trim_start_matches("0x"))?;
+        println!("Using RPC URL: {}", url);
+        // ws:// and wss:// URLs confirm on new-block notifications instead of polling
+        let http = http_options(&cli, resolve_rpc_headers(&cli.rpc_header)?);
+        let mut provider = transport::connect(&url, &http).await?;
+        if let Some(ms) = cli.poll_interval {
+            // Also paces the pending-transaction receipt polling of every step
+            provider = provider.interval(Duration::from_millis(ms));
+        }
+        check_connectivity(&provider, &url).await?;
 
-    let from_signing_key = SigningKey::from_bytes(from_key_bytes.as_slice().into())?;
-    let from_wallet: LocalWallet = LocalWallet::from(from_signing_key);
-    println!("Wallet address: {:?}", from_wallet.address());
+        match expected_chain_id {
+            Some(expected) => check_chain_id(&provider, expected).await?,
+            None => eprintln!("⚠️  No expected chain id for this network; skipping chain id check"),
+        }
+        if !transport::is_ws_url(&url) {
+            endpoint = Some((url, http));
+        }
+        provider
+    };
 
-    let chain_id = provider.get_chainid().await?.as_u32();
-    let signer: Arc<SignerMiddleware<_, _>> = Arc::new(SignerMiddleware::new(
-        provider.clone(),
-        from_wallet.with_chain_id(chain_id),
-    ));
+    let signers: Vec<Arc<SignerMiddleware<_, _>>> =
+        if cli.output_calldata.is_some() || cli.status || cli.fee_report || cli.simulate {
+            // Nothing is signed in these modes; calls only need a wallet to be built
+            vec![Arc::new(SignerMiddleware::new(
+                provider.clone(),
+                LocalWallet::new(&mut thread_rng()),
+            ))]
+        } else {
+            match &cli.keys {
+                Some(keys_path) => load_signers(provider.clone(), keys_path)
+                    .await?
+                    .into_iter()
+                    .map(Arc::new)
+                    .collect(),
+                None => {
+                    let source = if cli.key_stdin {
+                        KeySource::Stdin
+                    } else {
+                        KeySource::Env
+                    };
+                    vec![Arc::new(load_signer(provider.clone(), source).await?)]
+                }
+            }
+        };
+    // Independent statements are assigned round-robin by position; the main proof uses the
+    // first account
+    let accounts: Vec<Address> = signers.iter().map(|signer| signer.address()).collect();
 
-    // Load annotated proof - prioritize command line args, then env vars
-    let annotated_proof_path = cli
-        .annotated_proof
-        .or_else(|| env::var("ANNOTATED_PROOF").ok())
-        .expect("ANNOTATED_PROOF must be set in .env or use --annotated-proof <path>");
+    // A bundle replaces the individual annotated proof, input and fact topologies files
+    let bundle: Option<ProofBundle> = cli.bundle.as_deref().map(load_bundle).transpose()?;
 
-    println!("\n📄 Loading annotated proof:");
-    println!("  Path: {}", annotated_proof_path);
-    let origin_proof_file = read_to_string(&annotated_proof_path)?;
-    let file_size = origin_proof_file.len();
-    println!(
-        "  Size: {} bytes ({:.2} KB)",
-        file_size,
-        file_size as f64 / 1024.0
-    );
-    let annotated_proof: AnnotatedProof = serde_json::from_str(&origin_proof_file)?;
-    println!("  ✅ Annotated proof loaded successfully");
+    // Paths of the annotated proof and fact topologies, when they weren't bundled
+    let (annotated_proof, proof_paths, fact_topologies) = match &bundle {
+        Some(bundle) => {
+            let annotated_proof: AnnotatedProof =
+                serde_json::from_value(bundle.annotated_proof.clone())?;
+            let fact_topologies: Vec<FactTopology> =
+                serde_json::from_value(serde_json::to_value(&bundle.fact_topologies)?)?;
+            (annotated_proof, None, fact_topologies)
+        }
+        None => {
+            let annotated_proof_path = annotated_proof_path(&cli, &config).ok_or_else(|| {
+                VerifyError::config("ANNOTATED_PROOF must be set in .env or playground.toml, or use --annotated-proof <path>")
+            })?;
+            let fact_topologies_path = fact_topologies_path(&cli, &config).ok_or_else(|| {
+                VerifyError::config("FACT_TOPOLOGIES must be set in .env or playground.toml, or use --fact-topologies <path>")
+            })?;
+            let annotated_proof = load_annotated_proof(&annotated_proof_path)?;
+            let fact_topologies = load_fact_topologies(&fact_topologies_path)?;
+            (
+                annotated_proof,
+                Some((annotated_proof_path, fact_topologies_path)),
+                fact_topologies,
+            )
+        }
+    };
+    println!("  Fact topologies: {}", fact_topologies.len());
 
     // Generate split proofs
     println!("Splitting proof into smaller parts...");
-    let split_proofs = split_fri_merkle_statements(annotated_proof.clone())?;
+    let split_proofs = split_proof(annotated_proof.clone())?;
 
-    // Load fact topologies - prioritize command line args, then env vars
-    let fact_topologies_path = cli
-        .fact_topologies
-        .or_else(|| env::var("FACT_TOPOLOGIES").ok())
-        .expect("FACT_TOPOLOGIES must be set in .env or use --fact-topologies <path>");
+    // Load contract addresses from the local deployment, the bundle or deployment-addresses.json
+    // (optional when env vars or playground.toml provide them)
+    let deployment: serde_json::Value = match local_deployment
+        .or_else(|| bundle.as_ref().and_then(|b| b.deployment_addresses.clone()))
+    {
+        Some(deployment) => deployment,
+        None => match read_json_file("deployment-addresses.json") {
+            Ok(deployment) => deployment,
+            Err(e @ ConfigError::Parse { .. }) => return Err(e.into()),
+            Err(e) => {
+                eprintln!(
+                    "⚠️  Failed to read deployment-addresses.json: {}. Current directory: {:?}",
+                    e,
+                    std::env::current_dir()
+                );
+                serde_json::Value::Null
+            }
+        },
+    };
 
-    println!("\n📊 Loading fact topologies:");
-    println!("  Path: {}", fact_topologies_path);
-    let topologies_file = read_to_string(&fact_topologies_path).map_err(|e| {
-        format!(
-            "Failed to read fact_topologies.json from {}: {}",
-            fact_topologies_path, e
+    // Use deployed addresses - no defaults. A CLI flag wins over deployment-addresses.json,
+    // which wins over env vars, which win over playground.toml
+    let lookups = contract_lookups(&cli, &config);
+    println!("Loaded contract addresses:");
+    let memory_registry_address = resolve_address(&lookups.memory_registry, &deployment)?;
+    let memory_registry = parse_address("Memory Registry", &memory_registry_address)?;
+
+    if pages_only {
+        let plan = Plan::pages(&split_proofs, memory_registry, &accounts)?;
+        let total_steps = plan.transaction_count();
+        if !cli.yes && anvil.is_none() {
+            confirm_spend(&provider, &network, &accounts, &plan.transactions(), 1).await?;
+        }
+        let hooks = Hooks::new(&cli, total_steps);
+        let report = plan.send(&send_options, &signers, &hooks).await;
+        hooks.progress.finish();
+        hooks.write_outputs(&cli, total_steps, started, report.success)?;
+        if let Some(e) = report.failure {
+            return Err(e);
+        }
+        print_gas_summary(&report.steps);
+
+        println!(
+            "\n✅ All continuous pages registered; run verify without `pages` to verify the proof"
+        );
+        return Ok(());
+    }
+
+    let gps_verifier_address = resolve_address(&lookups.gps_verifier, &deployment)?;
+    let merkle_statement_address = resolve_address(&lookups.merkle_statement, &deployment)?;
+    let fri_statement_address = resolve_address(&lookups.fri_statement, &deployment)?;
+    let addresses = ContractAddresses {
+        merkle_statement: parse_address("Merkle Statement Contract", &merkle_statement_address)?,
+        fri_statement: parse_address("FRI Statement Contract", &fri_statement_address)?,
+        memory_registry,
+        gps_verifier: parse_address("GPS Verifier", &gps_verifier_address)?,
+    };
+
+    // Use the bundled or given input if present, otherwise prepare it directly from
+    // annotated_proof, with task_metadata computed from the fact topologies
+    let verifier_input = match (bundle, input_json_path(&cli, &config), proof_paths) {
+        (Some(bundle), _, _) => bundle.input,
+        (None, Some(input_path), _) => {
+            println!("\n📄 Loading verifier input: {}", input_path.display());
+            read_verifier_input(&input_path).map_err(VerifyError::Parse)?
+        }
+        (None, None, Some((annotated_proof_path, fact_topologies_path))) => {
+            prepare_verifier_input_with_topologies(&annotated_proof_path, &fact_topologies_path)
+                .map_err(VerifyError::Parse)?
+        }
+        (None, None, None) => unreachable!("annotated proof path is set when no bundle is given"),
+    };
+    // A mismatch means the file was corrupted or edited since it was prepared
+    verifier_input
+        .check_integrity()
+        .map_err(VerifyError::Parse)?;
+    if let Some(hash) = &verifier_input.integrity {
+        println!("  ✅ Integrity hash matches: {}", hash);
+    }
+    // Swapped in when the main proof is built; the integrity check covers the input file as
+    // prepared
+    let task_metadata = match &cli.task_metadata {
+        Some(path) => {
+            let task_metadata = load_task_metadata(path)?;
+            println!(
+                "  Using task_metadata from {} ({} words) instead of the input's",
+                path.display(),
+                task_metadata.len()
+            );
+            Some(task_metadata)
+        }
+        None => None,
+    };
+    let verify_config = VerifyConfig {
+        annotated_proof,
+        input: verifier_input,
+        addresses,
+        layout: cli.layout,
+        cairo_verifier_id: cli.cairo_verifier_id.map(U256::from),
+        task_metadata,
+        verify_signature: cli.verify_signature.clone(),
+        verify_args: cli.verify_arg.clone(),
+        fri_indices: cli.fri_indices.clone(),
+        send: send_options,
+    };
+    let verifier_input = verify_config.submitted_input();
+
+    // Otherwise only found when the main proof reverts, after every other step is paid for
+    let page_mismatches = check_page_counts(&verifier_input);
+    if page_mismatches.is_empty() {
+        println!("  ✅ Page counts agree across public_input, page facts and task_metadata");
+    } else {
+        eprintln!("⚠️  The main proof's page counts disagree, so it would revert (e.g. \"Invalid publicMemoryPages length\"):");
+        for mismatch in &page_mismatches {
+            eprintln!("    {}", mismatch);
+        }
+        if cli.strict {
+            return Err(VerifyError::Config(format!(
+                "{} page count mismatch(es) in the main proof's input",
+                page_mismatches.len()
+            )));
+        }
+    }
+
+    // Build every transaction up front so they can either be sent or exported in order
+    let mut plan = Plan::build(&verify_config, &split_proofs, &accounts)?;
+    if !cli.fri_indices.is_empty() {
+        eprintln!(
+            "⚠️  Only sending FRI statements {:?} of {}; the main proof fails unless the rest are already registered",
+            cli.fri_indices,
+            split_proofs.fri_merkle_statements.len()
+        );
+    }
+
+    if !cli.status {
+        let transactions = plan.transactions();
+        let sizes: Vec<(&str, usize)> = transactions
+            .iter()
+            .map(|(name, tx)| (name.as_str(), tx.data().map_or(0, |data| data.len())))
+            .collect();
+        check_calldata_sizes(&sizes, cli.max_calldata_size, cli.strict)
+            .map_err(VerifyError::Config)?;
+    }
+
+    let contracts = StatusContracts {
+        merkle_statement: addresses.merkle_statement,
+        fri_statement: addresses.fri_statement,
+        memory_registry: addresses.memory_registry,
+        gps_verifier: addresses.gps_verifier,
+    };
+    let trace_statements = plan
+        .statements
+        .iter()
+        .find(|(step, _)| *step == Step::Trace)
+        .map_or(&[][..], |(_, statements)| statements.as_slice());
+    if cli.status {
+        return print_status(
+            provider,
+            endpoint.as_ref().map(|(url, http)| (url.as_str(), http)),
+            &contracts,
+            trace_statements,
+            &split_proofs.fri_merkle_statements,
+            &verifier_input,
+        )
+        .await;
+    }
+
+    if cli.only_main {
+        eprintln!("⚠️  --only-main skips the trace, FRI and page statements; they must already be registered");
+        check_prerequisites(
+            &provider,
+            endpoint.as_ref().map(|(url, http)| (url.as_str(), http)),
+            &contracts,
+            trace_statements,
+            &split_proofs.fri_merkle_statements,
+            &verifier_input,
         )
+        .await?;
+        plan.statements.clear();
+    }
+
+    // Batch after --status, which looks up each statement's own fact
+    if let Some(multicall) = &cli.multicall {
+        let batching = MulticallBatching {
+            address: parse_address("Multicall3", multicall)?,
+            batch_size: cli.multicall_batch,
+            max_calldata: cli.max_calldata_size,
+        };
+        plan = plan.batched(&batching)?;
+        println!(
+            "Batching trace and FRI statements via Multicall3 at {:?}: {} transactions",
+            batching.address,
+            plan.statements
+                .iter()
+                .filter(|(step, _)| *step != Step::Pages)
+                .map(|(_, statements)| statements.len())
+                .sum::<usize>()
+        );
+    }
+
+    if cli.fee_report || cli.plan || cli.simulate {
+        let steps = plan.transactions();
+        if cli.fee_report {
+            return print_fee_report(&provider, &steps).await;
+        }
+        if cli.plan {
+            print_plan(&provider, &steps).await?;
+        }
+        if cli.simulate {
+            return simulate(&provider, &steps, cli.collect_failures).await;
+        }
+    }
+
+    if let Some(dir) = &cli.output_calldata {
+        let exported =
+            |name: &str, tx: &TypedTransaction| -> Result<(String, Address, Bytes), VerifyError> {
+                let to = *tx
+                    .to_addr()
+                    .ok_or_else(|| VerifyError::config(format!("{} has no target", name)))?;
+                let data = tx
+                    .data()
+                    .cloned()
+                    .ok_or_else(|| VerifyError::config(format!("{} has no calldata", name)))?;
+                Ok((name.to_string(), to, data))
+            };
+        let mut steps: Vec<(String, Address, Bytes)> = Vec::new();
+        let mut kinds = Vec::new();
+        for (step, statements) in &plan.statements {
+            for (name, tx) in statements {
+                steps.push(exported(name, tx)?);
+                kinds.push(step.kind());
+            }
+        }
+        if let Some(tx) = &plan.main_proof {
+            steps.push(exported(MAIN_PROOF, tx)?);
+            kinds.push("main-proof");
+        }
+
+        if cli.calldata_bundle {
+            write_calldata_bundle(dir, &steps, &kinds)?;
+        } else {
+            write_calldata(dir, &steps)?;
+        }
+        return Ok(());
+    }
+
+    let total_steps = plan.transaction_count();
+    let runs = cli.repeat.unwrap_or(1);
+    if !cli.yes && anvil.is_none() {
+        confirm_spend(&provider, &network, &accounts, &plan.transactions(), runs).await?;
+    }
+    let hooks = Hooks::new(&cli, total_steps * runs);
+
+    // Each run sends every step again; nothing is carried over from the runs before it
+    let mut receipts = Vec::new();
+    let mut samples = Vec::new();
+    let mut failure = None;
+    for run in 1..=runs {
+        if runs > 1 {
+            println!("\n🔁 Run {} of {}", run, runs);
+        }
+        let run_started = Instant::now();
+        let report = plan
+            .clone()
+            .send(&verify_config.send, &signers, &hooks)
+            .await;
+        if let Some(e) = report.failure {
+            failure = Some(e);
+            break;
+        }
+        samples.push(RunSample::new(run_started.elapsed(), &report.steps));
+        receipts.extend(report.steps);
+    }
+    hooks.progress.finish();
+    hooks.write_outputs(&cli, total_steps * runs, started, failure.is_none())?;
+    if runs > 1 {
+        print_repeat_summary(&samples);
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    print_gas_summary(&receipts);
+
+    println!("\n✅ All proof verification steps completed successfully!");
+    Ok(())
+}
+
+/// Read a --task-metadata override: a non-empty JSON array of 0x-prefixed hex words
+fn load_task_metadata(path: &Path) -> Result<Vec<U256>, VerifyError> {
+    let value: serde_json::Value = read_json_file(path)?;
+    let words = value.as_array().ok_or_else(|| {
+        VerifyError::config(format!(
+            "{}: task_metadata must be a JSON array of hex words",
+            path.display()
+        ))
     })?;
-    let file_size = topologies_file.len();
+    if words.is_empty() {
+        return Err(VerifyError::config(format!(
+            "{}: task_metadata is empty; the GPS verifier needs at least the task count",
+            path.display()
+        )));
+    }
+    words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            word.as_str()
+                .and_then(|word| word.strip_prefix("0x"))
+                .filter(|digits| !digits.is_empty())
+                .and_then(|digits| U256::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| {
+                    VerifyError::config(format!(
+                        "{}: task_metadata[{}] is {}, not a 0x-prefixed hex word",
+                        path.display(),
+                        index,
+                        word
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Client settings for HTTP RPC endpoints, with the already resolved `headers`
+fn http_options(cli: &Cli, headers: Vec<(String, String)>) -> HttpOptions {
+    HttpOptions {
+        headers,
+        timeout: cli.http_timeout.map(Duration::from_secs),
+        pool_max_idle_per_host: cli.http_pool_max_idle,
+        pool_idle_timeout: cli.http_pool_idle_timeout.map(Duration::from_secs),
+    }
+}
+
+/// Fail fast with a clear message when the RPC endpoint can't be reached, and print what it
+/// is connected to
+async fn check_connectivity(provider: &RpcProvider, url: &str) -> Result<(), VerifyError> {
+    let unreachable =
+        |e: ProviderError| VerifyError::Rpc(format!("RPC endpoint {} is unreachable: {}", url, e));
+    let block = provider.get_block_number().await.map_err(unreachable)?;
+    let chain_id = provider.get_chainid().await.map_err(unreachable)?;
+    // Not every node implements web3_clientVersion
+    let client_version = provider
+        .client_version()
+        .await
+        .unwrap_or_else(|_| "unknown".to_string());
     println!(
-        "  Size: {} bytes ({:.2} KB)",
-        file_size,
-        file_size as f64 / 1024.0
+        "🔌 Connected: {} (chain id {}, latest block {})",
+        client_version, chain_id, block
     );
-    let topology_json: serde_json::Value = serde_json::from_str(&topologies_file)?;
-    let fact_topologies: Vec<FactTopology> =
-        serde_json::from_value(topology_json.get("fact_topologies").unwrap().clone())?;
-    println!("  Count: {} fact topologies", fact_topologies.len());
-    println!("  ✅ Fact topologies loaded successfully");
+    Ok(())
+}
 
-    // Load contract addresses from deployment-addresses.json
-    let deployment_json = read_to_string("deployment-addresses.json").map_err(|e| {
-        format!(
-            "Failed to read deployment-addresses.json: {}. Current directory: {:?}",
-            e,
-            std::env::current_dir()
-        )
-    })?;
-    let deployment: serde_json::Value = serde_json::from_str(&deployment_json)?;
-
-    // Use deployed addresses - no defaults to avoid confusion
-    let merkle_statement_address = deployment.get("merkleStatementContract")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .or_else(|| env::var("MERKLE_STATEMENT_ADDRESS").ok())
-        .unwrap_or_else(|| {
-            panic!("merkleStatementContract not found in deployment-addresses.json and MERKLE_STATEMENT_ADDRESS not set");
-        });
+/// Ctrl-C tracking, the run report and progress, kept up to date as the steps are sent
+struct Hooks {
+    interrupt: Interrupt,
+    report: Report,
+    progress: StepProgress,
+}
 
-    let fri_statement_address = deployment.get("friStatementContract")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .or_else(|| env::var("FRI_STATEMENT_ADDRESS").ok())
-        .unwrap_or_else(|| {
-            panic!("friStatementContract not found in deployment-addresses.json and FRI_STATEMENT_ADDRESS not set");
-        });
+impl Hooks {
+    fn new(cli: &Cli, total_steps: usize) -> Hooks {
+        Hooks {
+            interrupt: Interrupt::install(),
+            report: Report::default(),
+            progress: StepProgress::new(total_steps, cli.quiet),
+        }
+    }
 
-    let memory_registry_address = deployment.get("factRegistry")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .or_else(|| env::var("MEMORY_REGISTRY_ADDRESS").ok())
-        .unwrap_or_else(|| {
-            panic!("factRegistry not found in deployment-addresses.json and MEMORY_REGISTRY_ADDRESS not set");
-        });
+    /// Write the --report and --metrics-out files, whether or not the run succeeded
+    fn write_outputs(
+        &self,
+        cli: &Cli,
+        total_steps: usize,
+        started: Instant,
+        success: bool,
+    ) -> Result<(), VerifyError> {
+        if let Some(report_path) = &cli.report {
+            self.report.write(report_path, success)?;
+        }
+        if let Some(metrics_path) = &cli.metrics_out {
+            RunMetrics {
+                report: &self.report,
+                total_steps,
+                duration: started.elapsed(),
+                success,
+            }
+            .write(metrics_path)?;
+        }
+        Ok(())
+    }
+}
 
-    let gps_verifier_address = deployment.get("gpsVerifier")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .or_else(|| env::var("GPS_VERIFIER_ADDRESS").ok())
-        .unwrap_or_else(|| {
-            panic!("gpsVerifier not found in deployment-addresses.json and GPS_VERIFIER_ADDRESS not set");
-        });
+impl StepHooks for Hooks {
+    fn stage(&self, stage: Stage) {
+        match stage {
+            Stage::Statements(step) => println!("{}", step.heading()),
+            Stage::Parallel {
+                statements,
+                accounts,
+            } => println!(
+                "Submitting {} statements from {} accounts in parallel:",
+                statements, accounts
+            ),
+            Stage::MainProof => println!("Verifying main proof:"),
+        }
+    }
 
-    println!("Loaded contract addresses from deployment-addresses.json:");
-    println!("  GPS Verifier: {}", gps_verifier_address);
-    println!("  Merkle Statement Contract: {}", merkle_statement_address);
-    println!("  FRI Statement Contract: {}", fri_statement_address);
-    println!("  Memory Registry: {}", memory_registry_address);
-
-    // Step 1: Verify trace decommitments
-    println!("Verifying trace decommitments:");
-    let merkle_contract_address = Address::from_str(&merkle_statement_address)?;
-    for i in 0..split_proofs.merkle_statements.len() {
-        let key = format!("Trace {}", i);
-        let trace_merkle = split_proofs
-            .merkle_statements
-            .get(&key)
-            .ok_or_else(|| format!("Trace {} not found", i))?;
-
-        let call = trace_merkle.verify(merkle_contract_address, signer.clone());
-        assert_call(call, &key).await?;
-    }
-
-    // Step 2: Verify FRI decommitments
-    println!("Verifying FRI decommitments:");
-    let fri_contract_address = Address::from_str(&fri_statement_address)?;
-    for (i, fri_statement) in split_proofs.fri_merkle_statements.iter().enumerate() {
-        let call = fri_statement.verify(fri_contract_address, signer.clone());
-        assert_call(call, &format!("FRI statement: {}", i)).await?;
-    }
-
-    // Step 3: Register continuous pages
-    let memory_fact_registry_address = Address::from_str(&memory_registry_address)?;
-    let (_, continuous_pages) = split_proofs.main_proof.memory_page_registration_args();
-    for (index, page) in continuous_pages.iter().enumerate() {
-        let register_continuous_pages_call =
-            split_proofs.main_proof.register_continuous_memory_page(
-                memory_fact_registry_address,
-                signer.clone(),
-                page.clone(),
-            );
-        let name = format!("register continuous page: {}", index);
-        assert_call(register_continuous_pages_call, &name).await?;
-    }
-
-    // Step 4: Verify main proof
-    println!("Verifying main proof:");
-    let gps_verifier_addr = Address::from_str(&gps_verifier_address)?;
-
-    // Use prepare_verifier_input to get VerifierInput directly from annotated_proof
-    let verifier_input = prepare_verifier_input(&annotated_proof_path);
-
-    let proof_params = verifier_input.proof_params;
-    let proof = verifier_input.proof;
-    let public_input = verifier_input.public_input;
-    let z = verifier_input.z;
-    let alpha = verifier_input.alpha;
-    let task_metadata = verifier_input.task_metadata;
-
-    // Create cairoAuxInput (public input + z + alpha) - same as test Forge
-    let mut cairo_aux_input = public_input.clone();
-    cairo_aux_input.push(z);
-    cairo_aux_input.push(alpha);
-
-    // Encode function call: verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256)
-    let function_selector = ethers::utils::keccak256(
-        "verifyProofAndRegister(uint256[],uint256[],uint256[],uint256[],uint256)",
-    )[..4]
-        .to_vec();
-    let encoded = ethers::abi::encode(&[
-        ethers::abi::Token::Array(
-            proof_params
-                .iter()
-                .map(|&v| ethers::abi::Token::Uint(v))
-                .collect(),
-        ),
-        ethers::abi::Token::Array(proof.iter().map(|&v| ethers::abi::Token::Uint(v)).collect()),
-        ethers::abi::Token::Array(
-            task_metadata
-                .iter()
-                .map(|&v| ethers::abi::Token::Uint(v))
-                .collect(),
-        ),
-        ethers::abi::Token::Array(
-            cairo_aux_input
-                .iter()
-                .map(|&v| ethers::abi::Token::Uint(v))
-                .collect(),
-        ),
-        ethers::abi::Token::Uint(U256::from(0u64)), // we can use 0 because we have only one verifier (starknet layout)
-    ]);
-
-    let call_data = [&function_selector[..], &encoded[..]].concat();
-    let tx = ethers::types::TransactionRequest::new()
-        .to(gps_verifier_addr)
-        .data(ethers::types::Bytes::from(call_data));
-
-    let pending_tx = signer.send_transaction(tx, None).await?;
-    println!("  Transaction sent, hash: {:?}", pending_tx.tx_hash());
-    let receipt = pending_tx.await?.ok_or("Transaction receipt not found")?;
-    match receipt.status {
-        Some(status) if status == U64::from(1) => {
-            println!("  ✅ Verified: Main proof");
-        }
-        Some(status) => {
-            return Err(format!("Transaction failed with status {}: Main proof", status).into());
+    fn sending(&self, _name: &str) {
+        self.interrupt.check();
+    }
+
+    fn sent(&self, name: &str, hash: TxHash) {
+        if name == MAIN_PROOF {
+            println!("  Transaction sent, hash: {:?}", hash);
         }
-        None => {
-            return Err("Transaction status unknown: Main proof".into());
+        self.interrupt.sent(name, hash);
+    }
+
+    fn notice(&self, name: &str, notice: Notice) {
+        let line = match notice {
+            Notice::Bumped {
+                hash,
+                after,
+                percent,
+                bumps,
+                max_bumps,
+            } => format!(
+                "  ⛽ {} not mined after {}s; bumped fees by {}% ({}/{}), hash: {:?}",
+                name,
+                after.as_secs(),
+                percent,
+                bumps,
+                max_bumps,
+                hash
+            ),
+            Notice::BumpsExhausted { bumps } => format!(
+                "  ⛽ {} still pending after {} bumps; waiting without bumping further",
+                name, bumps
+            ),
+            Notice::BumpCapped { cap } => format!(
+                "  ⛽ {} still pending; bumping again would pass --max-fee-cap {} gwei, waiting without bumping further",
+                name,
+                gwei(cap)
+            ),
+            Notice::StatusMissing { hash, retry } => format!(
+                "  ⚠️  {}: receipt for {:?} has no status; fetching it again in {}s",
+                name,
+                hash,
+                retry.as_secs()
+            ),
+            Notice::ConfirmedByFacts { hash, facts } => format!(
+                "  ⚠️  {}: receipt for {:?} has no status, but the {} fact(s) it registers are registered; counting it as confirmed",
+                name, hash, facts
+            ),
+        };
+        self.progress.println(&line);
+    }
+
+    fn confirmed(&self, name: &str, receipt: &TransactionReceipt) {
+        self.interrupt.completed(name);
+        if let Some(fact_hash) = self.report.record(name, receipt) {
+            // Through the progress bar, which a plain println would tear
+            self.progress
+                .println(&format!("  Registered fact hash: {:?}", fact_hash));
         }
+        self.progress.step_passed(name);
     }
 
-    println!("\n✅ All proof verification steps completed successfully!");
+    fn failed(&self, name: &str, error: &VerifyError) {
+        self.progress.step_failed(name, error);
+    }
+}
+
+/// Print the gas the confirmed steps used, the blocks they landed in and the costliest one
+fn print_gas_summary(receipts: &[StepReceipt]) {
+    let total_gas = receipts
+        .iter()
+        .filter_map(|receipt| receipt.gas_used)
+        .fold(U256::zero(), |sum, gas| sum + gas);
+    print!(
+        "\n⛽ {} gas over {} transactions",
+        total_gas,
+        receipts.len()
+    );
+    let blocks: Vec<U64> = receipts
+        .iter()
+        .filter_map(|receipt| receipt.block_number)
+        .collect();
+    if let (Some(first), Some(last)) = (blocks.iter().min(), blocks.iter().max()) {
+        print!(", in blocks {}..={}", first, last);
+    }
+    if let Some(confirmations) = receipts.iter().map(|receipt| receipt.confirmations).min() {
+        print!(" (each confirmed by {} blocks)", confirmations);
+    }
+    println!();
+
+    if let Some(costliest) = receipts.iter().max_by_key(|receipt| receipt.gas_used) {
+        println!(
+            "  Costliest: {}, {} gas ({:?})",
+            costliest.name,
+            costliest.gas_used.unwrap_or_default(),
+            costliest.tx_hash
+        );
+    }
+}
+
+/// Warn about (or with `strict`, reject) steps whose calldata exceeds `max_size` bytes
+fn check_calldata_sizes(
+    sizes: &[(&str, usize)],
+    max_size: usize,
+    strict: bool,
+) -> Result<(), String> {
+    let oversized: Vec<&(&str, usize)> =
+        sizes.iter().filter(|(_, size)| *size > max_size).collect();
+    if oversized.is_empty() {
+        return Ok(());
+    }
+
+    for (name, size) in &oversized {
+        eprintln!(
+            "⚠️  {} calldata is {} bytes ({:.2} KB), over the {} byte limit",
+            name,
+            size,
+            *size as f64 / 1024.0,
+            max_size
+        );
+    }
+    if strict {
+        return Err(format!(
+            "{} step(s) exceed --max-calldata-size {}; the proof is too large for this network",
+            oversized.len(),
+            max_size
+        ));
+    }
+    eprintln!("⚠️  These transactions may be rejected by the network or the RPC provider");
     Ok(())
 }
 
-async fn assert_call(
-    call: ContractFunctionCall,
-    name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    match call.send().await {
-        Ok(pending_tx) => match pending_tx.await {
-            Ok(mined_tx) => {
-                let tx_receipt = mined_tx.unwrap();
-                if tx_receipt.status.unwrap_or_default() == U64::from(1) {
-                    println!("Verified: {}", name);
-                    Ok(())
+/// Write each step as a `{to, data, value}` transaction request, numbered in send order
+fn write_calldata(dir: &Path, steps: &[(String, Address, Bytes)]) -> Result<(), VerifyError> {
+    fs::create_dir_all(dir)
+        .map_err(|e| VerifyError::io(format!("Failed to create {}", dir.display()), e))?;
+
+    println!("\n📝 Writing calldata to {}:", dir.display());
+    for (index, (name, to, data)) in steps.iter().enumerate() {
+        let slug: String = name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>()
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let path = dir.join(format!("{:03}-{}.json", index, slug));
+        let request = serde_json::json!({
+            "to": to,
+            "data": data,
+            "value": U256::zero(),
+        });
+        fs::write(&path, serde_json::to_string_pretty(&request)?)
+            .map_err(|e| VerifyError::io(format!("Failed to write {}", path.display()), e))?;
+        println!("  {} ({} bytes): {}", name, data.len(), path.display());
+    }
+
+    println!(
+        "\n✅ Wrote {} transaction requests; sign and broadcast them in file order",
+        steps.len()
+    );
+    Ok(())
+}
+
+/// Write `steps` as one ordered JSON array of EIP-2930-shaped transaction requests. The
+/// statements and pages don't depend on each other and may go in any order or together; the
+/// main proof, last, depends on every one of them being confirmed first.
+fn write_calldata_bundle(
+    dir: &Path,
+    steps: &[(String, Address, Bytes)],
+    kinds: &[&str],
+) -> Result<(), VerifyError> {
+    fs::create_dir_all(dir)
+        .map_err(|e| VerifyError::io(format!("Failed to create {}", dir.display()), e))?;
+
+    let main_proof = steps.len() - 1;
+    let transactions: Vec<serde_json::Value> = steps
+        .iter()
+        .zip(kinds)
+        .enumerate()
+        .map(|(order, ((name, to, data), kind))| {
+            let mut transaction = serde_json::json!({
+                "order": order,
+                "name": name,
+                "kind": kind,
+                "to": to,
+                "data": data,
+                "value": U256::zero(),
+                "accessList": [],
+                "dependsOn": if order == main_proof {
+                    (0..main_proof).collect::<Vec<_>>()
                 } else {
-                    Err(format!("Transaction failed: {}, but did not revert.", name).into())
-                }
+                    Vec::new()
+                },
+            });
+            if order == main_proof {
+                transaction["note"] = serde_json::json!(
+                    "Send only once every transaction in dependsOn is confirmed: the GPS verifier \
+                     reverts unless their statements and pages are registered"
+                );
             }
-            Err(e) => Err(decode_revert_message(e.into()).into()),
+            transaction
+        })
+        .collect();
+
+    let path = dir.join("transactions.json");
+    fs::write(&path, serde_json::to_string_pretty(&transactions)?)
+        .map_err(|e| VerifyError::io(format!("Failed to write {}", path.display()), e))?;
+    println!(
+        "\n✅ Wrote {} transactions to {}; the {} statements and pages may be submitted in any \
+         order, the main proof only after all of them are confirmed",
+        steps.len(),
+        path.display(),
+        main_proof
+    );
+    Ok(())
+}
+
+/// Parse a resolved contract address, naming the contract when it's malformed
+fn parse_address(label: &str, address: &str) -> Result<Address, VerifyError> {
+    Address::from_str(address)
+        .map_err(|e| VerifyError::Config(format!("Invalid {} address {}: {}", label, address, e)))
+}
+
+/// Where one contract address is looked up, in priority order
+struct AddressLookup<'a> {
+    label: &'static str,
+    cli_value: Option<&'a str>,
+    deployment_key: &'static str,
+    env_var: &'static str,
+    config_value: Option<&'a str>,
+    config_key: &'static str,
+}
+
+/// Lookups for every contract a run talks to
+struct ContractLookups<'a> {
+    memory_registry: AddressLookup<'a>,
+    gps_verifier: AddressLookup<'a>,
+    merkle_statement: AddressLookup<'a>,
+    fri_statement: AddressLookup<'a>,
+}
+
+impl<'a> ContractLookups<'a> {
+    fn all(&self) -> [&AddressLookup<'a>; 4] {
+        [
+            &self.memory_registry,
+            &self.gps_verifier,
+            &self.merkle_statement,
+            &self.fri_statement,
+        ]
+    }
+}
+
+fn contract_lookups<'a>(cli: &'a Cli, config: &'a Config) -> ContractLookups<'a> {
+    ContractLookups {
+        memory_registry: AddressLookup {
+            label: "Memory Registry",
+            cli_value: cli.fact_registry.as_deref(),
+            deployment_key: "factRegistry",
+            env_var: "MEMORY_REGISTRY_ADDRESS",
+            config_value: config.contracts.memory_registry.as_deref(),
+            config_key: "contracts.memory_registry",
+        },
+        gps_verifier: AddressLookup {
+            label: "GPS Verifier",
+            cli_value: cli.gps_verifier.as_deref(),
+            deployment_key: "gpsVerifier",
+            env_var: "GPS_VERIFIER_ADDRESS",
+            config_value: config.contracts.gps_verifier.as_deref(),
+            config_key: "contracts.gps_verifier",
+        },
+        merkle_statement: AddressLookup {
+            label: "Merkle Statement Contract",
+            cli_value: cli.merkle_statement.as_deref(),
+            deployment_key: "merkleStatementContract",
+            env_var: "MERKLE_STATEMENT_ADDRESS",
+            config_value: config.contracts.merkle_statement.as_deref(),
+            config_key: "contracts.merkle_statement",
+        },
+        fri_statement: AddressLookup {
+            label: "FRI Statement Contract",
+            cli_value: cli.fri_statement.as_deref(),
+            deployment_key: "friStatementContract",
+            env_var: "FRI_STATEMENT_ADDRESS",
+            config_value: config.contracts.fri_statement.as_deref(),
+            config_key: "contracts.fri_statement",
         },
-        Err(e) => Err(decode_revert_message(e).into()),
     }
 }
 
-fn decode_revert_message(
-    e: ContractError<SignerMiddleware<Provider<Http>, Wallet<SigningKey>>>,
-) -> String {
-    match e {
-        ContractError::Revert(err) => {
-            println!("Revert data: {:?}", err.0);
-            err.to_string()
+/// Find one contract address and where it came from: a CLI flag wins over
+/// deployment-addresses.json, which wins over the env var, which wins over playground.toml
+fn lookup_address(
+    lookup: &AddressLookup,
+    deployment: &serde_json::Value,
+) -> Option<(String, String)> {
+    lookup
+        .cli_value
+        .map(|v| (v.to_string(), "command line".to_string()))
+        .or_else(|| {
+            deployment
+                .get(lookup.deployment_key)
+                .and_then(|v| v.as_str())
+                .map(|v| (v.to_string(), "deployment-addresses.json".to_string()))
+        })
+        .or_else(|| {
+            env::var(lookup.env_var)
+                .ok()
+                .map(|v| (v, lookup.env_var.to_string()))
+        })
+        .or_else(|| {
+            lookup
+                .config_value
+                .map(|v| (v.to_string(), "playground.toml".to_string()))
+        })
+}
+
+/// Resolve one contract address and print it with where it came from
+fn resolve_address(
+    lookup: &AddressLookup,
+    deployment: &serde_json::Value,
+) -> Result<String, VerifyError> {
+    let (address, source) = lookup_address(lookup, deployment).ok_or_else(|| {
+        VerifyError::Config(format!(
+            "{} not found in deployment-addresses.json, {} not set and {} missing from playground.toml",
+            lookup.deployment_key, lookup.env_var, lookup.config_key
+        ))
+    })?;
+
+    println!("  {}: {} (from {})", lookup.label, address, source);
+    Ok(address)
+}
+
+/// Input files a run reads, resolved the same way the run resolves them
+fn watched_files(cli: &Cli, config: &Config) -> Vec<PathBuf> {
+    if let Some(bundle) = &cli.bundle {
+        return vec![PathBuf::from(bundle)];
+    }
+    annotated_proof_path(cli, config)
+        .into_iter()
+        .chain(fact_topologies_path(cli, config))
+        .map(PathBuf::from)
+        .chain(input_json_path(cli, config))
+        .collect()
+}
+
+/// Prepared input path - prioritize command line args, then playground.toml
+fn input_json_path(cli: &Cli, config: &Config) -> Option<PathBuf> {
+    cli.input_json
+        .clone()
+        .or_else(|| config.paths.input_json.as_ref().map(PathBuf::from))
+}
+
+/// Annotated proof path - prioritize command line args, then env vars, then playground.toml
+fn annotated_proof_path(cli: &Cli, config: &Config) -> Option<String> {
+    cli.annotated_proof
+        .clone()
+        .or_else(|| env::var("ANNOTATED_PROOF").ok())
+        .or_else(|| config.paths.annotated_proof.clone())
+}
+
+/// Fact topologies path - prioritize command line args, then env vars, then playground.toml
+fn fact_topologies_path(cli: &Cli, config: &Config) -> Option<String> {
+    cli.fact_topologies
+        .clone()
+        .or_else(|| env::var("FACT_TOPOLOGIES").ok())
+        .or_else(|| config.paths.fact_topologies.clone())
+}
+
+fn load_bundle(bundle_path: &str) -> Result<ProofBundle, VerifyError> {
+    println!("\n📦 Loading proof bundle:");
+    println!("  Path: {}", bundle_path);
+    let bundle: ProofBundle = read_json_file(bundle_path)?;
+    print_file_size(bundle_path);
+    println!("  ✅ Proof bundle loaded successfully");
+    Ok(bundle)
+}
+
+fn load_annotated_proof(annotated_proof_path: &str) -> Result<AnnotatedProof, VerifyError> {
+    println!("\n📄 Loading annotated proof:");
+    println!("  Path: {}", annotated_proof_path);
+    let annotated_proof: AnnotatedProof = read_json_file(annotated_proof_path)?;
+    print_file_size(annotated_proof_path);
+    println!("  ✅ Annotated proof loaded successfully");
+
+    Ok(annotated_proof)
+}
+
+fn load_fact_topologies(fact_topologies_path: &str) -> Result<Vec<FactTopology>, VerifyError> {
+    println!("\n📊 Loading fact topologies:");
+    println!("  Path: {}", fact_topologies_path);
+    let topology_json: serde_json::Value = read_json_file(fact_topologies_path)?;
+    print_file_size(fact_topologies_path);
+    let fact_topologies: Vec<FactTopology> = serde_json::from_value(
+        topology_json
+            .get("fact_topologies")
+            .ok_or_else(|| {
+                VerifyError::parse(format!(
+                    "{}: fact_topologies key missing",
+                    fact_topologies_path
+                ))
+            })?
+            .clone(),
+    )
+    .map_err(|e| VerifyError::parse(format!("{}: {}", fact_topologies_path, e)))?;
+    println!("  ✅ Fact topologies loaded successfully");
+
+    Ok(fact_topologies)
+}
+
+/// Print the size of the file at `path` as stored, compressed or not
+fn print_file_size(path: &str) {
+    if let Ok(metadata) = fs::metadata(path) {
+        let file_size = metadata.len();
+        println!(
+            "  Size: {} bytes ({:.2} KB)",
+            file_size,
+            file_size as f64 / 1024.0
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calldata_bundle_orders_the_main_proof_after_its_dependencies() {
+        let steps = vec![
+            (
+                "Trace 0".to_string(),
+                Address::repeat_byte(0x11),
+                Bytes::from(vec![0xde, 0xad]),
+            ),
+            (
+                "FRI 0".to_string(),
+                Address::repeat_byte(0x22),
+                Bytes::new(),
+            ),
+            (
+                "Main proof".to_string(),
+                Address::repeat_byte(0x33),
+                Bytes::from(vec![0xbe, 0xef]),
+            ),
+        ];
+        let dir = std::env::temp_dir().join(format!("verify_bundle_{}", std::process::id()));
+        let written = write_calldata_bundle(&dir, &steps, &["merkle", "fri", "main_proof"])
+            .map(|()| fs::read_to_string(dir.join("transactions.json")));
+        let _ = fs::remove_dir_all(&dir);
+        let transactions: Vec<serde_json::Value> =
+            serde_json::from_str(&written.unwrap().unwrap()).unwrap();
+
+        assert_eq!(transactions.len(), 3);
+        for (order, (transaction, (name, to, data))) in transactions.iter().zip(&steps).enumerate()
+        {
+            assert_eq!(transaction["order"], order);
+            assert_eq!(transaction["name"], name.as_str());
+            assert_eq!(transaction["to"], serde_json::json!(to));
+            assert_eq!(transaction["data"], serde_json::json!(data));
+            assert_eq!(transaction["value"], "0x0");
         }
-        _ => format!("Transaction failed: {:?}", e),
+        assert_eq!(transactions[1]["kind"], "fri");
+        assert_eq!(transactions[0]["dependsOn"], serde_json::json!([]));
+        assert_eq!(transactions[2]["dependsOn"], serde_json::json!([0, 1]));
+        assert!(transactions[0].get("note").is_none());
+        assert!(transactions[2]["note"].is_string());
     }
 }
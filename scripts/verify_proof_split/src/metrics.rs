@@ -0,0 +1,160 @@
+use crate::error::VerifyError;
+use crate::report::Report;
+use ethers::types::U256;
+use std::{collections::BTreeMap, fmt::Write, path::Path, time::Duration};
+
+/// Totals of one verification run, written by --metrics-out
+pub struct RunMetrics<'a> {
+    pub report: &'a Report,
+    pub total_steps: usize,
+    pub duration: Duration,
+    pub success: bool,
+}
+
+impl RunMetrics<'_> {
+    /// Write the metrics in Prometheus textfile format for node_exporter's textfile collector.
+    ///
+    /// The file is written next to `path` and renamed into place so the collector never
+    /// reads a partial file.
+    pub fn write(&self, path: &Path) -> Result<(), VerifyError> {
+        let steps = self.report.gas_used();
+        let total_gas = steps.iter().fold(U256::zero(), |sum, (_, gas)| sum + gas);
+        let mut by_category: BTreeMap<&str, U256> = BTreeMap::new();
+        for (name, gas) in &steps {
+            *by_category.entry(category(name)).or_default() += *gas;
+        }
+        // Steps stop at the first failure, so at most one step failed
+        let failed = usize::from(!self.success);
+
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        };
+        metric(
+            "verify_success",
+            "gauge",
+            "Whether the last verification run completed (1) or failed (0)",
+            u8::from(self.success).to_string(),
+        );
+        metric(
+            "verify_steps_total",
+            "gauge",
+            "Steps in the verification plan",
+            self.total_steps.to_string(),
+        );
+        metric(
+            "verify_steps_succeeded",
+            "gauge",
+            "Steps confirmed on-chain",
+            steps.len().to_string(),
+        );
+        metric(
+            "verify_steps_failed",
+            "gauge",
+            "Steps that failed",
+            failed.to_string(),
+        );
+        metric(
+            "verify_gas_used_total",
+            "gauge",
+            "Gas used by all confirmed steps",
+            total_gas.to_string(),
+        );
+        metric(
+            "verify_duration_seconds",
+            "gauge",
+            "Wall-clock duration of the run",
+            format!("{:.3}", self.duration.as_secs_f64()),
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP verify_category_gas_used Gas used by confirmed steps, per step category"
+        );
+        let _ = writeln!(out, "# TYPE verify_category_gas_used gauge");
+        for (category, gas) in &by_category {
+            let _ = writeln!(
+                out,
+                "verify_category_gas_used{{category=\"{}\"}} {}",
+                category, gas
+            );
+        }
+
+        let tmp = path.with_extension("prom.tmp");
+        std::fs::write(&tmp, out)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| {
+                VerifyError::io(format!("Failed to write metrics to {}", path.display()), e)
+            })?;
+        println!("📈 Metrics written to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Metric label for a step name
+fn category(name: &str) -> &'static str {
+    if name.starts_with("Trace") {
+        "trace"
+    } else if name.starts_with("FRI statement") {
+        "fri"
+    } else if name.starts_with("register continuous page") {
+        "memory_page"
+    } else {
+        "main_proof"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::TransactionReceipt;
+
+    #[test]
+    fn gas_is_totalled_per_category() {
+        let report = Report::default();
+        for (name, gas) in [
+            ("Trace 0", 100),
+            ("Trace 1", 200),
+            ("FRI statement: 0", 50),
+            ("FRI statement: 1", 60),
+            ("register continuous page 0", 7),
+            ("Main proof", 1000),
+        ] {
+            let receipt = TransactionReceipt {
+                gas_used: Some(U256::from(gas)),
+                ..Default::default()
+            };
+            report.record(name, &receipt);
+        }
+        let path = std::env::temp_dir().join(format!("verify_metrics_{}.prom", std::process::id()));
+        let metrics = RunMetrics {
+            report: &report,
+            total_steps: 7,
+            duration: Duration::from_millis(1500),
+            success: false,
+        };
+        metrics.write(&path).unwrap();
+        let out = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for expected in [
+            "verify_category_gas_used{category=\"trace\"} 300",
+            "verify_category_gas_used{category=\"fri\"} 110",
+            "verify_category_gas_used{category=\"memory_page\"} 7",
+            "verify_category_gas_used{category=\"main_proof\"} 1000",
+            "verify_gas_used_total 1417",
+            "verify_steps_succeeded 6",
+            "verify_steps_failed 1",
+            "verify_duration_seconds 1.500",
+        ] {
+            assert!(
+                out.lines().any(|line| line == expected),
+                "{}\n{}",
+                expected,
+                out
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::error::VerifyError;
+use crate::Statements;
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, Bytes},
+    utils::id,
+};
+
+/// Bytes aggregate3 adds around each call's own calldata: the tuple offset, target,
+/// allowFailure, calldata offset and calldata length words
+const PER_CALL_OVERHEAD: usize = 5 * 32;
+
+/// The Multicall3 contract the trace and FRI statements are batched through, and how large
+/// a batch may get
+#[derive(Debug, Clone, Copy)]
+pub struct MulticallBatching {
+    pub address: Address,
+    /// Most statements per batch
+    pub batch_size: usize,
+    /// Most calldata bytes per batch, where a statement fits at all
+    pub max_calldata: usize,
+}
+
+/// Fold independent `statements` into Multicall3 `aggregate3` calls on `multicall`, each of at
+/// most `batch_size` statements and (where a statement fits at all) `max_calldata` bytes.
+/// Failures aren't allowed, so a batch reverts as a whole if any statement in it does.
+pub fn batch(
+    statements: Statements,
+    multicall: Address,
+    batch_size: usize,
+    max_calldata: usize,
+) -> Result<Statements, VerifyError> {
+    let mut calls = Vec::new();
+    for (name, tx) in statements {
+        let to = *tx
+            .to_addr()
+            .ok_or_else(|| VerifyError::config(format!("{} has no target", name)))?;
+        let data = tx
+            .data()
+            .cloned()
+            .ok_or_else(|| VerifyError::config(format!("{} has no calldata", name)))?;
+        calls.push((name, to, data, tx));
+    }
+    let sizes: Vec<usize> = calls
+        .iter()
+        .map(|(_, _, data, _)| PER_CALL_OVERHEAD + data.len().div_ceil(32) * 32)
+        .collect();
+    let mut calls = calls.into_iter();
+    let batches: Vec<Vec<_>> = batch_lengths(&sizes, batch_size, max_calldata)
+        .into_iter()
+        .map(|length| calls.by_ref().take(length).collect())
+        .collect();
+
+    Ok(batches
+        .into_iter()
+        .map(|mut batch| {
+            if batch.len() == 1 {
+                let (name, _, _, tx) = batch.remove(0);
+                return (name, tx);
+            }
+            let calls: Vec<(Address, Bytes)> = batch
+                .iter()
+                .map(|(_, to, data, _)| (*to, data.clone()))
+                .collect();
+            let name = format!(
+                "{} .. {} ({} via multicall)",
+                batch[0].0,
+                batch[batch.len() - 1].0,
+                batch.len()
+            );
+            // Reuse the first statement's transaction for the batch; the sender sets its own
+            // `from`
+            let (_, _, _, mut tx) = batch.remove(0);
+            tx.set_to(multicall);
+            tx.set_data(aggregate3_calldata(&calls));
+            (name, tx)
+        })
+        .collect())
+}
+
+/// How many of the calls, of `sizes` encoded bytes each and taken in order, go in each batch:
+/// at most `batch_size` calls and `max_calldata` bytes, except that a call too large for any
+/// batch goes alone
+fn batch_lengths(sizes: &[usize], batch_size: usize, max_calldata: usize) -> Vec<usize> {
+    let mut lengths: Vec<usize> = Vec::new();
+    let mut batch_bytes = 0;
+    for &size in sizes {
+        match lengths.last_mut() {
+            Some(length) if *length < batch_size && batch_bytes + size <= max_calldata => {
+                *length += 1;
+                batch_bytes += size;
+            }
+            _ => {
+                lengths.push(1);
+                batch_bytes = size;
+            }
+        }
+    }
+    lengths
+}
+
+/// Calldata for `aggregate3((address target, bool allowFailure, bytes callData)[])` with
+/// failures disallowed
+fn aggregate3_calldata(calls: &[(Address, Bytes)]) -> Bytes {
+    let calls = Token::Array(
+        calls
+            .iter()
+            .map(|(to, data)| {
+                Token::Tuple(vec![
+                    Token::Address(*to),
+                    Token::Bool(false),
+                    Token::Bytes(data.to_vec()),
+                ])
+            })
+            .collect(),
+    );
+    let mut calldata = id("aggregate3((address,bool,bytes)[])").to_vec();
+    calldata.extend(encode(&[calls]));
+    calldata.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_split_on_count_and_calldata_size() {
+        assert_eq!(batch_lengths(&[100; 5], 2, 1000), [2, 2, 1]);
+        assert_eq!(batch_lengths(&[400, 400, 400, 900], 10, 1000), [2, 1, 1]);
+        // A call over the limit still goes, on its own
+        assert_eq!(batch_lengths(&[2000, 10, 10], 10, 1000), [1, 2]);
+        assert!(batch_lengths(&[], 10, 1000).is_empty());
+    }
+
+    #[test]
+    fn aggregate3_calldata_matches_golden_encoding() {
+        let calls = [
+            (
+                Address::repeat_byte(0x11),
+                Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+            ),
+            (Address::repeat_byte(0x22), Bytes::new()),
+        ];
+        let expected = concat!(
+            "82ad56cb",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000060",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "deadbeef00000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000002222222222222222222222222222222222222222",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000060",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        assert_eq!(hex::encode(aggregate3_calldata(&calls)), expected);
+    }
+}
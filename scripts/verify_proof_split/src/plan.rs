@@ -0,0 +1,73 @@
+use crate::error::VerifyError;
+use common::transport::RpcProvider;
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, U256},
+};
+
+/// Print every planned step with its calldata size and estimated gas, plus totals
+pub async fn print_plan(
+    provider: &RpcProvider,
+    steps: &[(String, TypedTransaction)],
+) -> Result<(), VerifyError> {
+    let width = steps
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Step".len());
+
+    println!("\n🗺️  Plan ({} steps):", steps.len());
+    println!(
+        "  {:>3}  {:<width$}  {:>12}  {:>12}",
+        "#",
+        "Step",
+        "Calldata",
+        "Gas",
+        width = width
+    );
+
+    let mut total_bytes = 0;
+    let mut total_gas = U256::zero();
+    let mut unestimated = 0;
+    for (index, (name, tx)) in steps.iter().enumerate() {
+        let size = tx.data().map_or(0, |data| data.len());
+        total_bytes += size;
+        // Later steps may depend on earlier ones being registered (the main proof always does)
+        let gas = match provider.estimate_gas(tx, None).await {
+            Ok(gas) => {
+                total_gas += gas;
+                gas.to_string()
+            }
+            Err(_) => {
+                unestimated += 1;
+                "unknown".to_string()
+            }
+        };
+        println!(
+            "  {:>3}  {:<width$}  {:>10} B  {:>12}",
+            index + 1,
+            name,
+            size,
+            gas,
+            width = width
+        );
+    }
+
+    println!(
+        "  {:>3}  {:<width$}  {:>10} B  {:>12}",
+        "",
+        "Total",
+        total_bytes,
+        total_gas,
+        width = width
+    );
+    if unestimated > 0 {
+        println!(
+            "  ({} step(s) could not be estimated before the earlier steps are registered)",
+            unestimated
+        );
+    }
+    println!();
+    Ok(())
+}
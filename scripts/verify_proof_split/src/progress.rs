@@ -0,0 +1,115 @@
+use crate::error::VerifyError;
+use indicatif::{ProgressBar, ProgressStyle};
+use owo_colors::{AnsiColors, OwoColorize};
+use std::env;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Step names are padded to this width so the statuses after them line up
+const NAME_WIDTH: usize = 24;
+
+/// "Step X of N" progress across the whole ordered plan, with an ETA from the average step
+/// time so far.
+///
+/// Draws a progress bar on a terminal; otherwise (or with --quiet) prints the counter on each
+/// step's status line. Statuses are colored on a terminal unless NO_COLOR is set.
+pub struct StepProgress {
+    bar: Option<ProgressBar>,
+    color: bool,
+    total: usize,
+    started: Instant,
+    done: Mutex<usize>,
+}
+
+impl StepProgress {
+    pub fn new(total: usize, quiet: bool) -> StepProgress {
+        let bar = (!quiet && std::io::stdout().is_terminal()).then(|| {
+            let bar = ProgressBar::new(total as u64);
+            bar.set_style(
+                ProgressStyle::with_template(
+                    "{spinner} [{elapsed_precise}] {bar:30} step {pos}/{len} (ETA {eta}) {msg}",
+                )
+                .expect("valid progress template"),
+            );
+            bar.enable_steady_tick(Duration::from_millis(200));
+            bar
+        });
+
+        // https://no-color.org: any non-empty NO_COLOR turns color off
+        let no_color = matches!(env::var_os("NO_COLOR"), Some(value) if !value.is_empty());
+        StepProgress {
+            bar,
+            color: !no_color && std::io::stdout().is_terminal(),
+            total,
+            started: Instant::now(),
+            done: Mutex::new(0),
+        }
+    }
+
+    /// Print a confirmed step's status line and advance the count
+    pub fn step_passed(&self, name: &str) {
+        let mut done = self.done.lock().unwrap();
+        *done += 1;
+
+        let status = self.paint("confirmed", AnsiColors::Green);
+        match &self.bar {
+            Some(bar) => {
+                bar.println(format!(
+                    "  ✅ {:<width$} {}",
+                    name,
+                    status,
+                    width = NAME_WIDTH
+                ));
+                bar.set_message(name.to_string());
+                bar.inc(1);
+            }
+            None => {
+                let average = self.started.elapsed() / *done as u32;
+                let eta = average * (self.total - *done) as u32;
+                println!(
+                    "  ✅ {:<width$} {}  [{}/{}, ETA {}s]",
+                    name,
+                    status,
+                    *done,
+                    self.total,
+                    eta.as_secs(),
+                    width = NAME_WIDTH
+                );
+            }
+        }
+    }
+
+    /// Print a failed step's status line
+    pub fn step_failed(&self, name: &str, error: &VerifyError) {
+        self.println(&format!(
+            "  ❌ {:<width$} {}: {}",
+            name,
+            self.paint("failed", AnsiColors::Red),
+            error,
+            width = NAME_WIDTH
+        ));
+    }
+
+    fn paint(&self, text: &str, color: AnsiColors) -> String {
+        if self.color {
+            text.color(color).bold().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Print a line without tearing the progress bar
+    pub fn println(&self, line: &str) {
+        match &self.bar {
+            Some(bar) => bar.println(line),
+            None => println!("{}", line),
+        }
+    }
+
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
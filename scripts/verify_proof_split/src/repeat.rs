@@ -0,0 +1,84 @@
+use crate::report::StepReceipt;
+use ethers::types::U256;
+use std::time::Duration;
+
+/// Duration and gas of one full verification in a --repeat run
+pub struct RunSample {
+    pub duration: Duration,
+    pub gas: U256,
+}
+
+impl RunSample {
+    pub fn new(duration: Duration, receipts: &[StepReceipt]) -> RunSample {
+        RunSample {
+            duration,
+            gas: receipts
+                .iter()
+                .filter_map(|receipt| receipt.gas_used)
+                .fold(U256::zero(), |sum, gas| sum + gas),
+        }
+    }
+}
+
+/// Min, max, mean and (population) standard deviation of a series
+struct Stats {
+    min: f64,
+    max: f64,
+    mean: f64,
+    stddev: f64,
+}
+
+impl Stats {
+    fn of(values: &[f64]) -> Stats {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        Stats {
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Print each run's time and gas, then their min/max/mean/stddev
+pub fn print_repeat_summary(samples: &[RunSample]) {
+    if samples.is_empty() {
+        return;
+    }
+
+    println!("\n📊 {} runs:", samples.len());
+    println!("  {:>4}  {:>10}  {:>14}", "Run", "Time (s)", "Gas");
+    for (index, sample) in samples.iter().enumerate() {
+        println!(
+            "  {:>4}  {:>10.2}  {:>14}",
+            index + 1,
+            sample.duration.as_secs_f64(),
+            sample.gas
+        );
+    }
+
+    let seconds: Vec<f64> = samples.iter().map(|s| s.duration.as_secs_f64()).collect();
+    let gas: Vec<f64> = samples.iter().map(|s| s.gas.as_u128() as f64).collect();
+    println!();
+    println!(
+        "  {:>8}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "", "min", "max", "mean", "stddev"
+    );
+    let time = Stats::of(&seconds);
+    println!(
+        "  {:>8}  {:>10.2}  {:>10.2}  {:>10.2}  {:>10.2}",
+        "Time (s)", time.min, time.max, time.mean, time.stddev
+    );
+    let gas = Stats::of(&gas);
+    println!(
+        "  {:>8}  {:>10.0}  {:>10.0}  {:>10.0}  {:>10.0}",
+        "Gas", gas.min, gas.max, gas.mean, gas.stddev
+    );
+    let runs_per_minute = 60.0 / time.mean;
+    println!(
+        "  Throughput: {:.2} verifications per minute",
+        runs_per_minute
+    );
+}
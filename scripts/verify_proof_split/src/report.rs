@@ -0,0 +1,108 @@
+use crate::error::VerifyError;
+use ethers::{
+    abi::{decode, ParamType},
+    types::{TransactionReceipt, TxHash, H256, U256, U64},
+    utils::keccak256,
+};
+use serde::Serialize;
+use std::{path::Path, sync::Mutex};
+
+/// Event MemoryPageFactRegistry emits for every registered continuous page
+const LOG_MEMORY_PAGE_FACT_CONTINUOUS: &str =
+    "LogMemoryPageFactContinuous(bytes32,uint256,uint256)";
+
+/// What a confirmed step's receipt says about it
+#[derive(Debug, Clone)]
+pub struct StepReceipt {
+    pub name: String,
+    pub tx_hash: TxHash,
+    pub block_number: Option<U64>,
+    pub gas_used: Option<U256>,
+    /// Blocks on top of the step's, counting its own, when it was taken as confirmed
+    pub confirmations: usize,
+}
+
+impl StepReceipt {
+    pub fn new(name: &str, receipt: &TransactionReceipt, confirmations: usize) -> StepReceipt {
+        StepReceipt {
+            name: name.to_string(),
+            tx_hash: receipt.transaction_hash,
+            block_number: receipt.block_number,
+            gas_used: receipt.gas_used,
+            confirmations,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StepReport {
+    name: String,
+    tx_hash: TxHash,
+    gas_used: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fact_hash: Option<H256>,
+}
+
+/// Per-step results of a verification run, written by --report
+#[derive(Debug, Default)]
+pub struct Report {
+    steps: Mutex<Vec<StepReport>>,
+}
+
+impl Report {
+    /// Record a confirmed step, returning the fact hash a page registration logged
+    pub fn record(&self, name: &str, receipt: &TransactionReceipt) -> Option<H256> {
+        let fact_hash = continuous_page_fact(receipt);
+        self.steps.lock().unwrap().push(StepReport {
+            name: name.to_string(),
+            tx_hash: receipt.transaction_hash,
+            gas_used: receipt.gas_used,
+            fact_hash,
+        });
+        fact_hash
+    }
+
+    /// Name and gas used of every confirmed step, in confirmation order
+    pub fn gas_used(&self) -> Vec<(String, U256)> {
+        self.steps
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|step| (step.name.clone(), step.gas_used.unwrap_or_default()))
+            .collect()
+    }
+
+    pub fn write(&self, path: &Path, success: bool) -> Result<(), VerifyError> {
+        let steps = self.steps.lock().unwrap();
+        let report = serde_json::json!({
+            "success": success,
+            "steps": *steps,
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&report)?).map_err(|e| {
+            VerifyError::io(format!("Failed to write report to {}", path.display()), e)
+        })?;
+        println!("📝 Report written to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Fact hash from the LogMemoryPageFactContinuous event in a page-registration receipt
+fn continuous_page_fact(receipt: &TransactionReceipt) -> Option<H256> {
+    let topic = H256::from(keccak256(LOG_MEMORY_PAGE_FACT_CONTINUOUS));
+    let log = receipt
+        .logs
+        .iter()
+        .find(|log| log.topics.first() == Some(&topic))?;
+    let tokens = decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+        ],
+        &log.data,
+    )
+    .ok()?;
+    let fact_hash = tokens.into_iter().next()?.into_fixed_bytes()?;
+    Some(H256::from_slice(&fact_hash))
+}
@@ -0,0 +1,359 @@
+use crate::bump::{send_with_bumps, GasBump};
+use crate::confirm::{refetch_receipt, wait_for_receipt, STATUS_RETRY_DELAY};
+use crate::error::VerifyError;
+use crate::facts::{call_fact, is_registered};
+use crate::fee_cap::check_fee_cap;
+use crate::layout::Step;
+use crate::report::StepReceipt;
+use crate::{step_error, Plan, VerifyReport, MAIN_PROOF};
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionReceipt, TxHash, U256},
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// How each step is sent and when it counts as confirmed
+#[derive(Debug, Clone)]
+pub struct SendOptions {
+    /// Blocks on top of each step's, counting its own, before the next step is sent
+    pub confirmations: usize,
+    /// Resubmit a step with higher fees when it isn't mined in time
+    pub bump: Option<GasBump>,
+    /// Don't send a step while the network's fee is above this many wei
+    pub fee_cap: Option<U256>,
+}
+
+impl Default for SendOptions {
+    /// One confirmation per step, no bumping and no fee cap
+    fn default() -> SendOptions {
+        SendOptions {
+            confirmations: 1,
+            bump: None,
+            fee_cap: None,
+        }
+    }
+}
+
+/// What a run is about to send, for callers that print headings
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    /// Every statement of one kind, from the only account
+    Statements(Step),
+    /// Every statement, spread across `accounts` accounts sending in parallel
+    Parallel { statements: usize, accounts: usize },
+    /// The main proof, from the first account
+    MainProof,
+}
+
+/// Something worth telling about a step while it's being sent
+#[derive(Debug, Clone, Copy)]
+pub enum Notice {
+    /// Not mined after `after`, so it was sent again as `hash` with fees `percent` higher
+    Bumped {
+        hash: TxHash,
+        after: Duration,
+        percent: u64,
+        bumps: usize,
+        max_bumps: usize,
+    },
+    /// Still pending after every bump allowed; the last submission is waited on
+    BumpsExhausted { bumps: usize },
+    /// Still pending, but another bump would pass the fee cap of `cap` wei
+    BumpCapped { cap: U256 },
+    /// The receipt came without a status; it is fetched again after `retry`
+    StatusMissing { hash: TxHash, retry: Duration },
+    /// Still no status, but the `facts` facts the step registers are registered
+    ConfirmedByFacts { hash: TxHash, facts: usize },
+}
+
+/// Called as a run goes, so a caller can show progress, keep a report or stop on Ctrl-C; the
+/// library itself prints nothing. Every method does nothing by default.
+pub trait StepHooks {
+    fn stage(&self, _stage: Stage) {}
+
+    /// Before `name` is sent; the last chance to stop the run cleanly
+    fn sending(&self, _name: &str) {}
+
+    /// Every hash broadcast for `name`, fee-bumped replacements included
+    fn sent(&self, _name: &str, _hash: TxHash) {}
+
+    fn notice(&self, _name: &str, _notice: Notice) {}
+
+    fn confirmed(&self, _name: &str, _receipt: &TransactionReceipt) {}
+
+    fn failed(&self, _name: &str, _error: &VerifyError) {}
+}
+
+/// Hooks that do nothing, for `verify_split_proof`
+pub struct NoHooks;
+
+impl StepHooks for NoHooks {}
+
+impl Plan {
+    /// Send every step with `options`' confirmations, gas bumping and fee cap, from `clients`'
+    /// default senders. One client sends the steps one at a time in order; with more, the
+    /// statements are spread round-robin and each client sends its share in order while the
+    /// clients run in parallel. The main proof goes last, from the first client.
+    ///
+    /// A step that fails ends the run: no further steps are sent, and those other clients
+    /// already sent are waited on. The report lists the steps confirmed so far.
+    pub async fn send<M: Middleware + 'static>(
+        self,
+        options: &SendOptions,
+        clients: &[Arc<M>],
+        hooks: &impl StepHooks,
+    ) -> VerifyReport {
+        let Some(first) = clients.first() else {
+            let error = VerifyError::config("No client to send the steps from");
+            return VerifyReport::new(Vec::new(), Some(error));
+        };
+        let sender = Sender { options, hooks };
+
+        let mut receipts = Vec::new();
+        if clients.len() > 1 {
+            let statements: Vec<(String, TypedTransaction)> = self
+                .statements
+                .into_iter()
+                .flat_map(|(_, statements)| statements)
+                .collect();
+            if !statements.is_empty() {
+                hooks.stage(Stage::Parallel {
+                    statements: statements.len(),
+                    accounts: clients.len(),
+                });
+            }
+            let (sent, failure) = sender.send_in_parallel(statements, clients).await;
+            receipts.extend(sent);
+            if failure.is_some() {
+                return VerifyReport::new(receipts, failure);
+            }
+        } else {
+            for (step, statements) in self.statements {
+                hooks.stage(Stage::Statements(step));
+                for (name, tx) in statements {
+                    match sender.send_statement(first, &name, tx).await {
+                        Ok(receipt) => receipts.push(receipt),
+                        Err(e) => return VerifyReport::new(receipts, Some(e)),
+                    }
+                }
+            }
+        }
+
+        if let Some(tx) = self.main_proof {
+            hooks.stage(Stage::MainProof);
+            let facts: Vec<(Address, [u8; 32])> = tx
+                .to_addr()
+                .map(|registry| {
+                    self.task_facts
+                        .iter()
+                        .map(|fact| (*registry, *fact))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match sender.send_step(first, MAIN_PROOF, tx, &facts).await {
+                Ok(receipt) => receipts.push(receipt),
+                Err(e) => return VerifyReport::new(receipts, Some(e)),
+            }
+        }
+        VerifyReport::new(receipts, None)
+    }
+}
+
+/// Sends single steps for one run
+struct Sender<'a, H> {
+    options: &'a SendOptions,
+    hooks: &'a H,
+}
+
+impl<H: StepHooks> Sender<'_, H> {
+    /// Send `statements` with statement `k` going to client `k % clients`, one sequential
+    /// stream per client, all clients concurrently. After a failure no stream sends another
+    /// statement; the first failure is returned with every receipt confirmed.
+    async fn send_in_parallel<M: Middleware + 'static>(
+        &self,
+        statements: Vec<(String, TypedTransaction)>,
+        clients: &[Arc<M>],
+    ) -> (Vec<StepReceipt>, Option<VerifyError>) {
+        let mut streams: Vec<Vec<(String, TypedTransaction)>> =
+            clients.iter().map(|_| Vec::new()).collect();
+        for (position, statement) in statements.into_iter().enumerate() {
+            streams[position % clients.len()].push(statement);
+        }
+
+        let stopped = AtomicBool::new(false);
+        let stopped = &stopped;
+        let runs = streams
+            .into_iter()
+            .zip(clients)
+            .map(|(stream, client)| async move {
+                let mut receipts = Vec::new();
+                for (name, tx) in stream {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match self.send_statement(client, &name, tx).await {
+                        Ok(receipt) => receipts.push(receipt),
+                        Err(e) => {
+                            stopped.store(true, Ordering::SeqCst);
+                            return (receipts, Some(e));
+                        }
+                    }
+                }
+                (receipts, None)
+            });
+
+        let mut receipts = Vec::new();
+        let mut failure = None;
+        for (sent, error) in futures::future::join_all(runs).await {
+            receipts.extend(sent);
+            failure = failure.or(error);
+        }
+        (receipts, failure)
+    }
+
+    /// Send a split statement; the fact it registers is checked when its receipt has no
+    /// status, where the calldata tells it
+    async fn send_statement<M: Middleware + 'static>(
+        &self,
+        client: &Arc<M>,
+        name: &str,
+        tx: TypedTransaction,
+    ) -> Result<StepReceipt, VerifyError> {
+        let facts: Vec<(Address, [u8; 32])> = tx
+            .to_addr()
+            .zip(tx.data().and_then(|data| call_fact(data)))
+            .map(|(registry, fact)| (*registry, fact))
+            .into_iter()
+            .collect();
+        self.send_step(client, name, tx, &facts).await
+    }
+
+    /// Send one step from `client`'s default sender and wait for it to be confirmed; `facts`
+    /// (registry, fact) are what it registers, checked when the receipt has no status
+    async fn send_step<M: Middleware + 'static>(
+        &self,
+        client: &Arc<M>,
+        name: &str,
+        tx: TypedTransaction,
+        facts: &[(Address, [u8; 32])],
+    ) -> Result<StepReceipt, VerifyError> {
+        self.hooks.sending(name);
+        let result = self.try_send_step(client, name, tx, facts).await;
+        match &result {
+            Ok(receipt) => self.hooks.confirmed(name, receipt),
+            Err(e) => self.hooks.failed(name, e),
+        }
+        result.map(|receipt| StepReceipt::new(name, &receipt, self.options.confirmations))
+    }
+
+    async fn try_send_step<M: Middleware + 'static>(
+        &self,
+        client: &Arc<M>,
+        name: &str,
+        mut tx: TypedTransaction,
+        facts: &[(Address, [u8; 32])],
+    ) -> Result<TransactionReceipt, VerifyError> {
+        // A multicall batch keeps the account of its first statement, which may not be the
+        // one sending it
+        let from = client
+            .default_sender()
+            .ok_or_else(|| VerifyError::config("The client has no default sender to sign with"))?;
+        tx.set_from(from);
+        if let Some(cap) = self.options.fee_cap {
+            check_fee_cap(client.as_ref(), cap, name).await?;
+        }
+
+        let confirmations = self.options.confirmations;
+        let receipt = match &self.options.bump {
+            Some(bump) => {
+                send_with_bumps(client.as_ref(), tx, bump, confirmations, name, self.hooks).await?
+            }
+            None => {
+                let hash = client
+                    .send_transaction(tx, None)
+                    .await
+                    .map_err(|e| step_error(name, e))?
+                    .tx_hash();
+                self.hooks.sent(name, hash);
+                wait_for_receipt(client.as_ref(), hash, confirmations)
+                    .await
+                    .map_err(|e| step_error(name, e))?
+                    .ok_or_else(|| {
+                        VerifyError::rpc(format!("Transaction receipt not found: {}", name))
+                    })?
+            }
+        };
+        self.checked_status(client, name, receipt, facts).await
+    }
+
+    /// Pass `receipt` on if its transaction succeeded. A receipt without a status is fetched
+    /// once more after a short delay; if it still has none, the step counts as confirmed only
+    /// when every one of `facts` (registry, fact) it should have registered is registered,
+    /// and is an error when there are none to check
+    async fn checked_status<M: Middleware + 'static>(
+        &self,
+        client: &Arc<M>,
+        name: &str,
+        receipt: TransactionReceipt,
+        facts: &[(Address, [u8; 32])],
+    ) -> Result<TransactionReceipt, VerifyError> {
+        let hash = receipt.transaction_hash;
+        let receipt = match receipt.status {
+            Some(_) => receipt,
+            None => {
+                self.hooks.notice(
+                    name,
+                    Notice::StatusMissing {
+                        hash,
+                        retry: STATUS_RETRY_DELAY,
+                    },
+                );
+                refetch_receipt(client.as_ref(), hash)
+                    .await?
+                    .unwrap_or(receipt)
+            }
+        };
+
+        match receipt.status {
+            Some(status) if status.as_u64() == 1 => Ok(receipt),
+            Some(status) => Err(VerifyError::revert(
+                name,
+                format!("transaction failed with status {}", status),
+            )),
+            None if facts.is_empty() => Err(VerifyError::rpc(format!(
+                "{}: receipt for {:?} has no status (post-state root {:?}) and the step's \
+                 effect can't be checked; look the transaction up before sending it again",
+                name, hash, receipt.root
+            ))),
+            None => {
+                for (registry, fact) in facts {
+                    if !is_registered(client.clone(), *registry, *fact).await? {
+                        return Err(VerifyError::revert(
+                            name,
+                            format!(
+                                "receipt for {:?} has no status and fact 0x{} is not registered in {:?}",
+                                hash,
+                                ethers::utils::hex::encode(fact),
+                                registry
+                            ),
+                        ));
+                    }
+                }
+                self.hooks.notice(
+                    name,
+                    Notice::ConfirmedByFacts {
+                        hash,
+                        facts: facts.len(),
+                    },
+                );
+                Ok(receipt)
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::VerifyError;
+use common::transport::RpcProvider;
+use ethers::{
+    abi::{decode, ParamType},
+    providers::{Middleware, MiddlewareError, ProviderError},
+    types::transaction::eip2718::TypedTransaction,
+};
+
+/// Selector of the `Error(string)` revert payload `require` and `revert` produce
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Run every step as an eth_call against the latest block, without sending anything. Stops at
+/// the first step that would revert, or with `collect_failures` runs all of them and lists
+/// every failure with its revert reason at the end.
+pub async fn simulate(
+    provider: &RpcProvider,
+    steps: &[(String, TypedTransaction)],
+    collect_failures: bool,
+) -> Result<(), VerifyError> {
+    println!("\n🧪 Simulating {} steps (nothing is sent):", steps.len());
+    let mut failures = Vec::new();
+    for (name, tx) in steps {
+        match provider.call(tx, None).await {
+            Ok(_) => println!("  ✅ {}", name),
+            Err(e) => {
+                let reason = revert_reason(e)?;
+                println!("  ❌ {}: {}", name, reason);
+                if !collect_failures {
+                    return Err(VerifyError::revert(name, reason));
+                }
+                failures.push((name, reason));
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        println!("\n✅ Every step would pass");
+        return Ok(());
+    }
+    println!(
+        "\n❌ {} of {} steps would revert:",
+        failures.len(),
+        steps.len()
+    );
+    for (name, reason) in &failures {
+        println!("  {}: {}", name, reason);
+    }
+    // Each call runs against the current chain state, not after the steps before it
+    println!("  (the main proof only passes once every statement before it is registered)");
+    Err(VerifyError::revert(
+        "Simulation",
+        format!("{} of {} steps would revert", failures.len(), steps.len()),
+    ))
+}
+
+/// Why a simulated call reverted: the decoded `Error(string)` message, the raw revert data,
+/// or the node's error message. Errors that aren't reverts end the simulation.
+fn revert_reason(e: ProviderError) -> Result<String, VerifyError> {
+    let Some(response) = e.as_error_response() else {
+        return Err(VerifyError::rpc(&e));
+    };
+    let Some(data) = response.as_revert_data() else {
+        return Ok(response.message.clone());
+    };
+    let message = data
+        .strip_prefix(&ERROR_STRING_SELECTOR)
+        .and_then(|payload| decode(&[ParamType::String], payload).ok())
+        .and_then(|tokens| tokens.into_iter().next()?.into_string());
+    Ok(match message {
+        Some(message) => message,
+        None if data.is_empty() => response.message.clone(),
+        None => format!("revert data {}", data),
+    })
+}
@@ -0,0 +1,55 @@
+use crate::error::VerifyError;
+use crate::fee_report::eth;
+use common::{transport::RpcProvider, Network, LOCAL_CHAIN_IDS};
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, U256},
+};
+
+/// Estimate what sending `steps` `runs` times from `accounts` costs at the current gas price
+/// and ask before anything is sent; local chains are never asked about
+pub async fn confirm_spend(
+    provider: &RpcProvider,
+    network: &Network,
+    accounts: &[Address],
+    steps: &[(String, TypedTransaction)],
+    runs: usize,
+) -> Result<(), VerifyError> {
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if LOCAL_CHAIN_IDS.contains(&chain_id) {
+        return Ok(());
+    }
+
+    let gas_price = provider.get_gas_price().await?;
+    let mut total_gas = U256::zero();
+    let mut unestimated = 0;
+    for (_, tx) in steps {
+        // Later steps may depend on earlier ones being registered (the main proof always does)
+        match provider.estimate_gas(tx, None).await {
+            Ok(gas) => total_gas += gas,
+            Err(_) => unestimated += 1,
+        }
+    }
+    let cost = eth(total_gas * gas_price * U256::from(runs));
+    let cost = match unestimated {
+        0 => format!("{} ETH", cost),
+        n => format!(
+            "at least {} ETH ({} step(s) can't be estimated before the earlier ones are registered)",
+            cost, n
+        ),
+    };
+    let from = match accounts {
+        [only] => format!("{:?}", only),
+        [first, rest @ ..] => format!("{:?} and {} other account(s)", first, rest.len()),
+        [] => "no account".to_string(),
+    };
+
+    common::confirm_spend(&format!(
+        "About to send {} transactions from {} on {}, estimated cost {}.",
+        steps.len() * runs,
+        from,
+        network.describe(chain_id),
+        cost
+    ))?;
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use crate::error::VerifyError;
+use crate::facts::{is_registered, merkle_statement_fact, word};
+use common::transport::{batch_request, RpcProvider};
+use common::HttpOptions;
+use ethers::{
+    types::{transaction::eip2718::TypedTransaction, Address, U256},
+    utils::{hex, id, keccak256},
+};
+use prepare_input::{gps_task_facts, VerifierInput};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+/// Contracts whose registries are queried
+pub struct StatusContracts {
+    pub merkle_statement: Address,
+    pub fri_statement: Address,
+    pub memory_registry: Address,
+    pub gps_verifier: Address,
+}
+
+/// isValid lookups per JSON-RPC batch, well under the 100 or so public endpoints accept
+const FACT_BATCH_SIZE: usize = 50;
+
+/// Rounds of retrying the lookups that failed, each only for those
+const FACT_BATCH_RETRIES: u64 = 3;
+
+/// Print which statements, pages and task facts are already registered, using only eth_call.
+/// Over HTTP (`endpoint`) the lookups go out in JSON-RPC batches.
+pub async fn print_status(
+    provider: RpcProvider,
+    endpoint: Option<(&str, &HttpOptions)>,
+    contracts: &StatusContracts,
+    trace_statements: &[(String, TypedTransaction)],
+    fri_statements: &[impl Serialize],
+    verifier_input: &VerifierInput,
+) -> Result<(), VerifyError> {
+    let mut rows = prerequisite_rows(contracts, trace_statements, fri_statements, verifier_input);
+
+    match gps_task_facts(verifier_input) {
+        Ok(facts) => {
+            for (task, fact) in facts.into_iter().enumerate() {
+                rows.push((
+                    format!("Main proof: task {} fact", task),
+                    Some((contracts.gps_verifier, fact)),
+                ));
+            }
+        }
+        Err(e) => {
+            eprintln!("⚠️  Could not compute task facts: {}", e);
+            rows.push(("Main proof".to_string(), None));
+        }
+    }
+
+    let queries: Vec<(Address, [u8; 32])> = rows.iter().filter_map(|(_, query)| *query).collect();
+    let mut results = lookup_facts(&provider, endpoint, &queries)
+        .await?
+        .into_iter();
+    let rows: Vec<(String, Option<bool>)> = rows
+        .into_iter()
+        .map(|(name, query)| (name, query.and_then(|_| results.next().flatten())))
+        .collect();
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    println!("\n📋 Registration status:");
+    for (name, status) in &rows {
+        let label = match status {
+            Some(true) => "✅ registered",
+            Some(false) => "⬜ not registered",
+            None => "❔ unknown",
+        };
+        println!("  {:<width$}  {}", name, label, width = width);
+    }
+
+    let registered = rows.iter().filter(|(_, s)| *s == Some(true)).count();
+    println!("\n{} of {} already registered", registered, rows.len());
+    Ok(())
+}
+
+/// Look up every trace, FRI and page fact the main proof needs, failing if any isn't
+/// registered, for a run that sends only the main proof. A fact that can't be computed or
+/// looked up is warned about and assumed registered.
+pub async fn check_prerequisites(
+    provider: &RpcProvider,
+    endpoint: Option<(&str, &HttpOptions)>,
+    contracts: &StatusContracts,
+    trace_statements: &[(String, TypedTransaction)],
+    fri_statements: &[impl Serialize],
+    verifier_input: &VerifierInput,
+) -> Result<(), VerifyError> {
+    let rows = prerequisite_rows(contracts, trace_statements, fri_statements, verifier_input);
+    let queries: Vec<(Address, [u8; 32])> = rows.iter().filter_map(|(_, query)| *query).collect();
+    println!(
+        "Checking the {} statements and pages the main proof needs are registered...",
+        rows.len()
+    );
+    let mut results = lookup_facts(provider, endpoint, &queries)
+        .await?
+        .into_iter();
+
+    let mut missing = Vec::new();
+    let mut unknown = Vec::new();
+    for (name, query) in rows {
+        match query.and_then(|_| results.next().flatten()) {
+            Some(true) => {}
+            Some(false) => missing.push(name),
+            None => unknown.push(name),
+        }
+    }
+    if !unknown.is_empty() {
+        eprintln!(
+            "⚠️  Could not tell whether these are registered; assuming they are: {}",
+            unknown.join(", ")
+        );
+    }
+    if !missing.is_empty() {
+        return Err(VerifyError::Config(format!(
+            "--only-main needs every statement and page registered, but {} of them aren't: {}. \
+             Run without --only-main to send them",
+            missing.len(),
+            missing.join(", ")
+        )));
+    }
+    println!("  ✅ Statements and pages are registered");
+    Ok(())
+}
+
+/// A row per trace statement, FRI statement and continuous page, with its registry and fact
+/// where the fact could be computed
+fn prerequisite_rows(
+    contracts: &StatusContracts,
+    trace_statements: &[(String, TypedTransaction)],
+    fri_statements: &[impl Serialize],
+    verifier_input: &VerifierInput,
+) -> Vec<(String, Option<(Address, [u8; 32])>)> {
+    let mut rows: Vec<(String, Option<(Address, [u8; 32])>)> = Vec::new();
+
+    for (name, tx) in trace_statements {
+        let fact = tx.data().and_then(|data| merkle_statement_fact(data));
+        rows.push((
+            name.clone(),
+            fact.map(|fact| (contracts.merkle_statement, fact)),
+        ));
+    }
+
+    for (i, statement) in fri_statements.iter().enumerate() {
+        let fact = fri_statement_fact(statement);
+        rows.push((
+            format!("FRI statement: {}", i),
+            fact.map(|fact| (contracts.fri_statement, fact)),
+        ));
+    }
+
+    for (index, fact_hash) in verifier_input.memory_page_fact_hashes.iter().enumerate() {
+        let fact = parse_bytes32(fact_hash);
+        rows.push((
+            format!("continuous page: {}", index),
+            fact.map(|fact| (contracts.memory_registry, fact)),
+        ));
+    }
+
+    rows
+}
+
+/// Whether each (registry, fact) in `queries` is registered, in order. Over HTTP they are
+/// batched, and the entries a batch fails are retried on their own; one that keeps failing
+/// is None. Over a WebSocket each is its own eth_call.
+async fn lookup_facts(
+    provider: &RpcProvider,
+    endpoint: Option<(&str, &HttpOptions)>,
+    queries: &[(Address, [u8; 32])],
+) -> Result<Vec<Option<bool>>, VerifyError> {
+    let Some((url, options)) = endpoint else {
+        let client = Arc::new(provider.clone());
+        let mut results = Vec::new();
+        for (registry, fact) in queries {
+            results.push(Some(is_registered(client.clone(), *registry, *fact).await?));
+        }
+        return Ok(results);
+    };
+
+    let mut results = vec![None; queries.len()];
+    let mut pending: Vec<usize> = (0..queries.len()).collect();
+    let mut last_error = String::new();
+    for attempt in 0..=FACT_BATCH_RETRIES {
+        if pending.is_empty() {
+            break;
+        }
+        if attempt > 0 {
+            println!(
+                "  Retrying {} failed lookup(s) ({}/{})",
+                pending.len(),
+                attempt,
+                FACT_BATCH_RETRIES
+            );
+            tokio::time::sleep(Duration::from_secs(attempt)).await;
+        }
+
+        let batches = pending.chunks(FACT_BATCH_SIZE).count();
+        let mut failed = Vec::new();
+        for (batch_index, batch) in pending.chunks(FACT_BATCH_SIZE).enumerate() {
+            let requests: Vec<(&str, Value)> = batch
+                .iter()
+                .map(|&index| ("eth_call", is_valid_call(queries[index])))
+                .collect();
+            match batch_request(url, options, &requests).await {
+                Ok(responses) => {
+                    for (&index, response) in batch.iter().zip(responses) {
+                        match response.and_then(|result| decode_bool(&result)) {
+                            Ok(valid) => results[index] = Some(valid),
+                            Err(e) => {
+                                last_error = e;
+                                failed.push(index);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    last_error = e.to_string();
+                    failed.extend_from_slice(batch);
+                }
+            }
+            println!(
+                "  Looked up batch {}/{} ({} facts)",
+                batch_index + 1,
+                batches,
+                batch.len()
+            );
+        }
+        pending = failed;
+    }
+
+    if !pending.is_empty() {
+        eprintln!(
+            "⚠️  {} lookup(s) still failing after {} retries ({}); shown as unknown",
+            pending.len(),
+            FACT_BATCH_RETRIES,
+            last_error
+        );
+    }
+    Ok(results)
+}
+
+/// eth_call params for `isValid(fact)` on `registry`, at the latest block
+fn is_valid_call((registry, fact): (Address, [u8; 32])) -> Value {
+    let mut data = id("isValid(bytes32)").to_vec();
+    data.extend_from_slice(&fact);
+    json!([
+        { "to": format!("{:?}", registry), "data": format!("0x{}", hex::encode(data)) },
+        "latest"
+    ])
+}
+
+/// The bool an eth_call returned, ABI-encoded as one word
+fn decode_bool(result: &Value) -> Result<bool, String> {
+    let word = result
+        .as_str()
+        .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+        .filter(|bytes| bytes.len() == 32)
+        .ok_or_else(|| format!("unexpected isValid result {}", result))?;
+    Ok(!U256::from_big_endian(&word).is_zero())
+}
+
+/// Fact registered by FriStatementContract.verifyFRI:
+/// keccak256(evaluationPoint, friStepSize, keccak256(inputQueue), keccak256(outputQueue), root)
+fn fri_statement_fact(statement: &impl Serialize) -> Option<[u8; 32]> {
+    let value = serde_json::to_value(statement).ok()?;
+    let field = |name: &str| -> Option<U256> { as_u256(value.get(name)?) };
+    let queue_hash = |name: &str| -> Option<[u8; 32]> {
+        let queue = value
+            .get(name)?
+            .as_array()?
+            .iter()
+            .map(as_u256)
+            .collect::<Option<Vec<U256>>>()?;
+        // Only whole (index, value, inverse) triples are hashed; drop any trailing sentinel
+        let triples = queue.len() / 3 * 3;
+        Some(keccak256(
+            queue[..triples]
+                .iter()
+                .flat_map(|v| word(*v))
+                .collect::<Vec<u8>>(),
+        ))
+    };
+
+    let mut data = Vec::with_capacity(5 * 32);
+    data.extend_from_slice(&word(field("evaluation_point")?));
+    data.extend_from_slice(&word(field("fri_step_size")?));
+    data.extend_from_slice(&queue_hash("input_interleaved")?);
+    data.extend_from_slice(&queue_hash("output_interleaved")?);
+    data.extend_from_slice(&word(field("expected_root")?));
+    Some(keccak256(data))
+}
+
+/// U256 from a serialized statement field, which may be a hex string or a plain number
+fn as_u256(value: &Value) -> Option<U256> {
+    match value {
+        Value::String(s) => U256::from_str(s).ok(),
+        Value::Number(n) => n.as_u64().map(U256::from),
+        _ => None,
+    }
+}
+
+fn parse_bytes32(hex: &str) -> Option<[u8; 32]> {
+    let bytes = ethers::utils::hex::decode(hex.trim_start_matches("0x")).ok()?;
+    bytes.try_into().ok()
+}
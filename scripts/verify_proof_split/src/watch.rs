@@ -0,0 +1,91 @@
+use crate::error::VerifyError;
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long to wait for a burst of writes (e.g. a prover rewriting its output) to settle
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Re-run this binary with `args` (a --simulate run) once now and again whenever one of
+/// `files` changes, printing whether each run passed. Runs until interrupted.
+pub fn run(files: &[PathBuf], args: &[String]) -> Result<(), VerifyError> {
+    if files.is_empty() {
+        return Err(VerifyError::config(
+            "Nothing to watch: set --annotated-proof, --fact-topologies, --input-json or --bundle",
+        ));
+    }
+    let files: Vec<PathBuf> = files
+        .iter()
+        .map(|file| absolute(file))
+        .collect::<Result<_, _>>()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| VerifyError::Config(format!("Failed to start file watcher: {}", e)))?;
+    // Watch the directories: editors and provers often replace a file rather than write it,
+    // which would end a watch on the file itself
+    let mut dirs: Vec<&Path> = files.iter().filter_map(|file| file.parent()).collect();
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| {
+                VerifyError::Config(format!("Failed to watch {}: {}", dir.display(), e))
+            })?;
+    }
+
+    println!("👀 Watching:");
+    for file in &files {
+        println!("  {}", file.display());
+    }
+    run_once(args)?;
+
+    loop {
+        let event = rx
+            .recv()
+            .map_err(|_| VerifyError::config("File watcher stopped"))?;
+        let touches_input =
+            matches!(&event, Ok(event) if event.paths.iter().any(|path| files.contains(path)));
+        if !touches_input {
+            continue;
+        }
+
+        // Coalesce the rest of the burst into this run
+        while rx.recv_timeout(SETTLE_TIME).is_ok() {}
+        println!("\n🔁 Input changed, re-running...");
+        run_once(args)?;
+    }
+}
+
+fn run_once(args: &[String]) -> Result<(), VerifyError> {
+    let exe = std::env::current_exe()?;
+    let status = Command::new(&exe)
+        .args(args)
+        .status()
+        .map_err(|e| VerifyError::io(format!("Failed to run {}", exe.display()), e))?;
+    if status.success() {
+        println!("\n✅ Would pass");
+    } else {
+        println!("\n❌ Would fail ({})", status);
+    }
+    println!("👀 Waiting for changes (Ctrl-C to stop)...");
+    Ok(())
+}
+
+/// `file` as an absolute path with its directory resolved, the form watcher events report
+fn absolute(file: &Path) -> Result<PathBuf, VerifyError> {
+    let dir = match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| VerifyError::io(format!("Failed to watch {}", file.display()), e))?;
+    let name = file
+        .file_name()
+        .ok_or_else(|| VerifyError::Config(format!("Not a file: {}", file.display())))?;
+    Ok(dir.join(name))
+}
@@ -173,7 +173,10 @@ contract CpuVerifierTest is Test {
     
     function test_VerifyProof() public {
         // Load input.json (prepared by prepare-input binary)
-        string memory inputJson = vm.readFile("./input.json");
+        // VERIFIER_TEST_INPUT lets the test runner point at an example without touching
+        // ./input.json; not INPUT_JSON, which .env sets for the scripts
+        string memory inputPath = vm.envOr("VERIFIER_TEST_INPUT", string("./input.json"));
+        string memory inputJson = vm.readFile(inputPath);
         require(bytes(inputJson).length > 0, "input.json is empty or not found");
 
         // Parse proofParams, proof, publicInput from input.json